@@ -5,4 +5,6 @@ pub enum RoundResult {
 
 pub enum LostReason {
     RoundsExceeded,
+    /// A rock fell into a pit, or there are not enough rocks left to cover all targets
+    RockLost,
 }
@@ -1,11 +1,13 @@
+mod link;
 mod map_data;
 mod round_state;
 mod win_condition;
 
 pub use self::{
+    link::Link,
     map_data::MapData,
-    round_state::MapState,
-    win_condition::{GeneralWinConditions, RockWinConditions, WinCondition},
+    round_state::{MapState, MapStateDiff},
+    win_condition::{DiagonalTilt, GeneralWinConditions, RockWinConditions, WinCondition},
 };
 
 pub struct W<T>(pub T);
@@ -0,0 +1,36 @@
+use maps::prelude::{Offset, Pos, Transform};
+use serde::{Deserialize, Serialize};
+
+/// Connects a pressure plate or a switch with the doors it opens and closes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Link {
+    pub trigger: Pos,
+    pub doors: Vec<Pos>,
+}
+
+impl Link {
+    /// Moves the linked positions along with a map of the given size that gets transformed.
+    #[must_use]
+    pub fn transform(self, transform: Transform, width: u32, height: u32) -> Self {
+        Self {
+            trigger: transform.map_pos(self.trigger, width, height),
+            doors: self
+                .doors
+                .into_iter()
+                .map(|pos| transform.map_pos(pos, width, height))
+                .collect(),
+        }
+    }
+
+    /// Moves the trigger and doors by `offset`. Doors outside of `width` x `height` are dropped,
+    /// and the whole link is gone if its trigger is.
+    #[must_use]
+    pub fn shift(self, offset: Offset, width: u32, height: u32) -> Option<Self> {
+        let shift_pos = |pos: &Pos| maps::W(pos).try_add_within(&offset, width, height);
+
+        Some(Self {
+            trigger: shift_pos(&self.trigger)?,
+            doors: self.doors.iter().filter_map(shift_pos).collect(),
+        })
+    }
+}
@@ -1,28 +1,137 @@
-use std::{fmt::Debug, str::FromStr};
+use std::fmt::Debug;
 
 use classes::EnumerateU32;
 use console::{style, Style};
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Serialize};
 
-use maps::prelude::{Map, Pos, RockKind, Tile};
+use maps::prelude::{
+    map_formats, Map, MapParseError, Offset, Portals, Pos, RockColor, RockKind, Tile, Transform,
+    URect,
+};
 
-use crate::{MapState, RockWinConditions, WinCondition, W};
+use crate::{Link, MapState, RockWinConditions, WinCondition, W};
 
-#[derive(Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Serialize, Deserialize)]
+#[serde(try_from = "LevelFile")]
 pub struct MapData {
-    #[serde(deserialize_with = "load_map_from_str")]
+    /// Either the level text, an array of rows of tiles or a sparse map, see [`map_formats`]
     pub map: Map,
     pub win: WinCondition,
+    /// Which doors are opened and closed by the pressure plates and switches
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<Link>,
+    /// The portal pairs of `map`
+    #[serde(skip)]
+    portals: Portals,
+}
+
+/// [`MapData`] like it is written in the level files, before the portals are paired up.
+#[derive(Deserialize)]
+#[serde(rename = "MapData", deny_unknown_fields)]
+struct LevelFile {
+    #[serde(deserialize_with = "map_formats::any_format::deserialize")]
+    map: Map,
+    win: WinCondition,
+    #[serde(default)]
+    links: Vec<Link>,
+}
+
+impl TryFrom<LevelFile> for MapData {
+    type Error = MapParseError;
+
+    fn try_from(level: LevelFile) -> Result<Self, Self::Error> {
+        let portals = level.map.validate_portals()?;
+
+        Ok(Self {
+            map: level.map,
+            win: level.win,
+            links: level.links,
+            portals,
+        })
+    }
 }
 
-fn load_map_from_str<'de, D>(deserializer: D) -> Result<Map, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let buf = String::deserialize(deserializer)?;
+impl MapData {
+    /// Pairs up the portals of `map`, see [`Map::portals`].
+    #[must_use]
+    pub fn new(map: Map, win: WinCondition, links: Vec<Link>) -> Self {
+        Self {
+            portals: map.portals(),
+            map,
+            win,
+            links,
+        }
+    }
+
+    /// The portal pairs of the map, which are kept up to date when the map is transformed or cropped.
+    #[must_use]
+    pub const fn portals(&self) -> &Portals {
+        &self.portals
+    }
+
+    /// Transforms the map together with the positions of the win conditions and links.
+    #[must_use]
+    pub fn transform(self, transform: Transform) -> Self {
+        let (width, height) = (self.map.width(), self.map.height());
+
+        Self::new(
+            self.map.transform(transform),
+            WinCondition {
+                general: self.win.general.transform(transform),
+                rocks: self.win.rocks.transform(transform, width, height),
+            },
+            self.links
+                .into_iter()
+                .map(|link| link.transform(transform, width, height))
+                .collect(),
+        )
+    }
+
+    /// The part of the level inside `rect`, see [`Map::crop`].
+    /// The win positions and links are moved along, and the ones outside of `rect` are dropped.
+    #[must_use]
+    pub fn crop(self, rect: URect) -> Self {
+        let view = self.map.view(rect);
+        let (map, offset) = (view.to_map(), -view.rect().min.as_ivec2());
 
-    Map::<Tile>::from_str(&buf).map_err(serde::de::Error::custom)
+        self.with_shifted_map(map, offset)
+    }
+
+    /// Surrounds the level with `margin` cells of `fill` on every side, see [`Map::pad`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the padded map is too big.
+    #[must_use]
+    pub fn pad(self, margin: u16, fill: Option<Tile>) -> Self {
+        let map = self.map.pad(margin, fill);
+
+        self.with_shifted_map(map, Offset::splat(i32::from(margin)))
+    }
+
+    /// Changes the size of the level, keeping the top left corner in place, see [`Map::resize`].
+    #[must_use]
+    pub fn resize(self, width: u32, height: u32, fill: Option<Tile>) -> Self {
+        let map = self.map.resize(width, height, fill);
+
+        self.with_shifted_map(map, Offset::ZERO)
+    }
+
+    fn with_shifted_map(self, map: Map, offset: Offset) -> Self {
+        let (width, height) = (map.width(), map.height());
+
+        Self::new(
+            map,
+            WinCondition {
+                general: self.win.general,
+                rocks: self.win.rocks.shift(offset, width, height),
+            },
+            self.links
+                .into_iter()
+                .filter_map(|link| link.shift(offset, width, height))
+                .collect(),
+        )
+    }
 }
 
 impl Debug for W<(&MapData, &MapState)> {
@@ -34,26 +143,43 @@ impl Debug for W<(&MapData, &MapState)> {
             if f.alternate() {
                 f.write_str(
                     &(row
-                        .map(ToString::to_string)
+                        .iter()
                         .enumerate_u32()
-                        .map(|(x, tile)| (Pos { x, y: row_index }, tile))
+                        .map(|(x, cell)| {
+                            let tile_pos = Pos { x, y: row_index };
+                            let tile = cell.as_ref().map_or_else(
+                                || " ".to_owned(),
+                                |tile| self.0 .1.current_tile(&tile_pos, tile).to_string(),
+                            );
+
+                            (tile_pos, tile)
+                        })
                         .map(|(tile_pos, mut tile)| {
+                            let rock_color = self.0 .1.rock_colors.get(&tile_pos);
+
                             if self.0 .1.rock_positions.contains(&tile_pos) {
-                                tile = Tile {
-                                    rock: RockKind::RoundRock,
-                                }
-                                .to_string();
+                                tile =
+                                    Tile::from(rock_color.map_or(RockKind::RoundRock, |color| {
+                                        RockKind::ColoredRock(*color)
+                                    }))
+                                    .to_string();
                             }
 
-                            match &self.0 .0.win.rocks {
-                                RockWinConditions::Pos(win_pos) => {
+                            match (&self.0 .0.win.rocks, rock_color) {
+                                (RockWinConditions::Exit(_), _) => todo!(),
+                                (_, Some(color)) => rock_color_style(*color).apply_to(tile),
+                                (RockWinConditions::Pos(win_pos), None) => {
                                     if win_pos.contains(&tile_pos) {
                                         win_tile_style.apply_to(tile)
                                     } else {
                                         style(tile)
                                     }
                                 }
-                                RockWinConditions::Exit(_) => todo!(),
+                                (RockWinConditions::Colored(targets), None) => targets
+                                    .iter()
+                                    .find(|(target, _)| *target == tile_pos)
+                                    .map_or_else(Style::new, |(_, color)| rock_color_style(*color))
+                                    .apply_to(tile),
                             }
                         })
                         .map(|x| x.to_string())
@@ -62,10 +188,18 @@ impl Debug for W<(&MapData, &MapState)> {
                         + "\n"),
                 )?;
             } else {
-                f.write_fmt(format_args!("{:?}\n", row.collect::<Vec<_>>()))?;
+                f.write_fmt(format_args!("{row:?}\n"))?;
             }
         }
 
         Ok(())
     }
 }
+
+fn rock_color_style(color: RockColor) -> Style {
+    match color {
+        RockColor::Red => Style::new().color256(160), // Red3 #d70000 rgb(215,0,0)
+        RockColor::Green => Style::new().color256(34), // Green3 #00af00 rgb(0,175,0)
+        RockColor::Blue => Style::new().color256(33), // DodgerBlue1 #0087ff rgb(0,135,255)
+    }
+}
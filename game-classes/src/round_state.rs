@@ -1,8 +1,185 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
 
-use maps::prelude::Pos;
+use maps::prelude::{Floor, Offset, Pos, RockColor, RockKind, Tile, Transform};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct MapState {
     pub rock_positions: HashSet<Pos>,
+    /// The colors of the rocks which have one
+    pub rock_colors: HashMap<Pos, RockColor>,
+    /// Shallow pits which swallowed a rock and are normal floor now
+    pub filled_pits: HashSet<Pos>,
+    /// How many rocks fell into pits
+    pub lost_rocks: u32,
+    /// How many hit points the cracked walls lost
+    pub wall_damage: HashMap<Pos, u8>,
+    /// The doors which are open right now
+    pub open_doors: HashSet<Pos>,
+    /// Switches which were flipped an odd number of times
+    pub flipped_switches: HashSet<Pos>,
+}
+
+/// How the rocks moved between two states, as found by [`MapState::diff`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MapStateDiff {
+    /// Rocks which rolled from the first to the second position
+    pub moved_rocks: Vec<(Pos, Pos)>,
+    /// Rocks which are only in the old state, e.g. because they fell into a pit
+    pub removed_rocks: Vec<Pos>,
+    /// Rocks which are only in the new state
+    pub added_rocks: Vec<Pos>,
+}
+
+impl MapStateDiff {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.moved_rocks.is_empty() && self.removed_rocks.is_empty() && self.added_rocks.is_empty()
+    }
+}
+
+impl Display for MapStateDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (from, to) in &self.moved_rocks {
+            writeln!(f, "({}, {}) -> ({}, {})", from.x, from.y, to.x, to.y)?;
+        }
+        for pos in &self.removed_rocks {
+            writeln!(f, "- ({}, {})", pos.x, pos.y)?;
+        }
+        for pos in &self.added_rocks {
+            writeln!(f, "+ ({}, {})", pos.x, pos.y)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl MapState {
+    /// Which rocks moved from where to where to get from this state to `other`.
+    ///
+    /// Rocks have no identity, so every rock which left a position is paired with the nearest rock
+    /// of the same color that arrived at another position. Rocks without a partner are removed or added.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> MapStateDiff {
+        let row_major = |pos: &Pos| (pos.y, pos.x);
+
+        let mut removed_rocks = self
+            .rock_positions
+            .difference(&other.rock_positions)
+            .copied()
+            .collect::<Vec<_>>();
+        removed_rocks.sort_unstable_by_key(row_major);
+
+        let mut added_rocks = other
+            .rock_positions
+            .difference(&self.rock_positions)
+            .copied()
+            .collect::<Vec<_>>();
+        added_rocks.sort_unstable_by_key(row_major);
+
+        let mut moved_rocks = Vec::new();
+        removed_rocks.retain(|from| {
+            let color = self.rock_colors.get(from);
+            let Some((nearest, _)) = added_rocks
+                .iter()
+                .enumerate()
+                .filter(|(_, to)| other.rock_colors.get(to) == color)
+                .min_by_key(|(_, to)| from.x.abs_diff(to.x) + from.y.abs_diff(to.y))
+            else {
+                return true;
+            };
+
+            moved_rocks.push((*from, added_rocks.remove(nearest)));
+            false
+        });
+
+        MapStateDiff {
+            moved_rocks,
+            removed_rocks,
+            added_rocks,
+        }
+    }
+
+    /// How `tile` on `pos` looks like after pits were filled, cracked walls were damaged and doors were moved.
+    #[must_use]
+    pub fn current_tile(&self, pos: &Pos, tile: &Tile) -> Tile {
+        let mut tile = tile.clone();
+
+        if self.filled_pits.contains(pos) {
+            tile.floor = Floor::Normal;
+        }
+
+        if let (RockKind::CrackedWall(hit_points), Some(damage)) =
+            (tile.object, self.wall_damage.get(pos))
+        {
+            tile.object = match hit_points.saturating_sub(*damage) {
+                0 => RockKind::Empty,
+                hit_points => RockKind::CrackedWall(hit_points),
+            };
+        }
+
+        if let RockKind::Door { .. } = tile.object {
+            tile.object = RockKind::Door {
+                open: self.open_doors.contains(pos),
+            };
+        }
+
+        tile
+    }
+
+    /// Moves the rocks along with a map of the given size that gets transformed.
+    #[must_use]
+    pub fn transform(self, transform: Transform, width: u32, height: u32) -> Self {
+        let map_all = |positions: HashSet<Pos>| {
+            positions
+                .into_iter()
+                .map(|pos| transform.map_pos(pos, width, height))
+                .collect()
+        };
+
+        Self {
+            rock_positions: map_all(self.rock_positions),
+            rock_colors: self
+                .rock_colors
+                .into_iter()
+                .map(|(pos, color)| (transform.map_pos(pos, width, height), color))
+                .collect(),
+            filled_pits: map_all(self.filled_pits),
+            lost_rocks: self.lost_rocks,
+            wall_damage: self
+                .wall_damage
+                .into_iter()
+                .map(|(pos, damage)| (transform.map_pos(pos, width, height), damage))
+                .collect(),
+            open_doors: map_all(self.open_doors),
+            flipped_switches: map_all(self.flipped_switches),
+        }
+    }
+
+    /// The state of the same round on a map which was cropped or padded like in [`MapData::crop`](crate::MapData::crop).
+    #[must_use]
+    pub fn shift(self, offset: Offset, width: u32, height: u32) -> Self {
+        let shift_pos = |pos: &Pos| maps::W(pos).try_add_within(&offset, width, height);
+        let shift_all = |positions: HashSet<Pos>| positions.iter().filter_map(shift_pos).collect();
+
+        Self {
+            rock_positions: shift_all(self.rock_positions),
+            rock_colors: self
+                .rock_colors
+                .into_iter()
+                .filter_map(|(pos, color)| Some((shift_pos(&pos)?, color)))
+                .collect(),
+            filled_pits: shift_all(self.filled_pits),
+            lost_rocks: self.lost_rocks,
+            wall_damage: self
+                .wall_damage
+                .into_iter()
+                .filter_map(|(pos, damage)| Some((shift_pos(&pos)?, damage)))
+                .collect(),
+            open_doors: shift_all(self.open_doors),
+            flipped_switches: shift_all(self.flipped_switches),
+        }
+    }
 }
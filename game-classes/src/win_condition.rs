@@ -1,19 +1,115 @@
-use maps::prelude::Pos;
-use serde::Deserialize;
+use maps::prelude::{Direction, Offset, Pos, RockColor, Transform, Transformable, Wrap};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WinCondition {
     pub general: GeneralWinConditions,
     pub rocks: RockWinConditions,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RockWinConditions {
     Pos(Vec<Pos>),
     Exit(Vec<Pos>),
+    /// Every target needs a rock with the same color on it
+    Colored(Vec<(Pos, RockColor)>),
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GeneralWinConditions {
     pub max_moves: Option<u32>,
+    /// How many tiles a rock can roll in one tilt. Rocks keep sliding on ice though.
+    #[serde(default)]
+    pub max_roll_distance: Option<u32>,
+    /// Whether rocks may fall into pits without losing the level
+    #[serde(default)]
+    pub allow_rock_loss: bool,
+    /// Whether splitters create a clone of the rock, which leaves the splitter on the other side
+    #[serde(default)]
+    pub clone_split_rocks: bool,
+    /// Which edges of the map lead rocks back onto the opposite edge
+    #[serde(default)]
+    pub wrap: Wrap,
+    /// Whether the level can be tilted diagonally as well
+    #[serde(default)]
+    pub diagonal_tilt: DiagonalTilt,
+}
+
+/// Whether a level can be tilted diagonally, and how the rocks get past corners then.
+///
+/// A rock which rolls diagonally passes the two tiles beside its path, e.g. the tiles to the right and below
+/// of it when it rolls to the bottom right. A tile blocks the corner if a rock couldn't roll onto it,
+/// like walls, closed doors, reflectors or void. Other rocks never block corners.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DiagonalTilt {
+    /// The level can only be tilted in the four straight directions
+    #[default]
+    Off,
+    /// Rocks pass a single blocking tile, but can't squeeze through between two of them
+    On,
+    /// Rocks squeeze through between two blocking tiles as well
+    Squeeze,
+}
+
+impl GeneralWinConditions {
+    /// Adjusts the conditions to a map that gets transformed, e.g. a horizontal wrap becomes vertical.
+    #[must_use]
+    pub fn transform(self, transform: Transform) -> Self {
+        Self {
+            wrap: self.wrap.transformed(transform),
+            ..self
+        }
+    }
+
+    /// The directions in which the level can be tilted.
+    #[must_use]
+    pub const fn tilt_directions(&self) -> &'static [Direction] {
+        match self.diagonal_tilt {
+            DiagonalTilt::Off => &Direction::STRAIGHT,
+            DiagonalTilt::On | DiagonalTilt::Squeeze => &Direction::ALL,
+        }
+    }
+}
+
+impl RockWinConditions {
+    /// Moves the win positions along with a map of the given size that gets transformed.
+    #[must_use]
+    pub fn transform(self, transform: Transform, width: u32, height: u32) -> Self {
+        let map_all = |positions: Vec<Pos>| {
+            positions
+                .into_iter()
+                .map(|pos| transform.map_pos(pos, width, height))
+                .collect()
+        };
+
+        match self {
+            Self::Pos(positions) => Self::Pos(map_all(positions)),
+            Self::Exit(positions) => Self::Exit(map_all(positions)),
+            Self::Colored(targets) => Self::Colored(
+                targets
+                    .into_iter()
+                    .map(|(pos, color)| (transform.map_pos(pos, width, height), color))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Moves the win positions by `offset`, dropping the ones outside of `width` x `height`.
+    /// Used by [`MapData::crop`](crate::MapData::crop) and the other resizing methods.
+    #[must_use]
+    pub fn shift(self, offset: Offset, width: u32, height: u32) -> Self {
+        let shift_pos = |pos: &Pos| maps::W(pos).try_add_within(&offset, width, height);
+        let shift_all = |positions: Vec<Pos>| positions.iter().filter_map(shift_pos).collect();
+
+        match self {
+            Self::Pos(positions) => Self::Pos(shift_all(positions)),
+            Self::Exit(positions) => Self::Exit(shift_all(positions)),
+            Self::Colored(targets) => Self::Colored(
+                targets
+                    .into_iter()
+                    .filter_map(|(pos, color)| Some((shift_pos(&pos)?, color)))
+                    .collect(),
+            ),
+        }
+    }
 }
@@ -2,7 +2,9 @@ use bevy_math::IVec2;
 
 use crate::prelude::Offset;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
 pub enum Horizontal {
     Top,
     Left,
@@ -11,6 +13,8 @@ pub enum Horizontal {
 }
 
 impl Horizontal {
+    pub const ALL: [Self; 4] = [Self::Top, Self::Left, Self::Right, Self::Bottom];
+
     #[must_use]
     pub const fn to_offset(self) -> Offset {
         match self {
@@ -20,9 +24,21 @@ impl Horizontal {
             Self::Bottom => Offset::Y,
         }
     }
+
+    #[must_use]
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::Top => Self::Bottom,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::Bottom => Self::Top,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
 pub enum Diagonal {
     TopLeft,
     TopRight,
@@ -31,13 +47,31 @@ pub enum Diagonal {
 }
 
 impl Diagonal {
+    pub const ALL: [Self; 4] = [
+        Self::TopLeft,
+        Self::TopRight,
+        Self::BottomLeft,
+        Self::BottomRight,
+    ];
+
+    /// The offset of the diagonal neighbour, which is the sum of the offsets of both [`Diagonal::horizontals`].
     #[must_use]
     pub const fn to_offset(self) -> IVec2 {
         match self {
-            Self::TopLeft => IVec2::new(1, -1),
-            Self::TopRight => IVec2::new(1, 1),
+            Self::TopLeft => IVec2::new(-1, -1),
+            Self::TopRight => IVec2::new(1, -1),
             Self::BottomLeft => IVec2::new(-1, 1),
-            Self::BottomRight => IVec2::new(-1, -1),
+            Self::BottomRight => IVec2::new(1, 1),
+        }
+    }
+
+    #[must_use]
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::TopLeft => Self::BottomRight,
+            Self::TopRight => Self::BottomLeft,
+            Self::BottomLeft => Self::TopRight,
+            Self::BottomRight => Self::TopLeft,
         }
     }
 
@@ -50,4 +84,140 @@ impl Diagonal {
             Self::BottomRight => [Horizontal::Bottom, Horizontal::Right],
         }
     }
+
+    #[must_use]
+    pub const fn from_horizontals(first: Horizontal, second: Horizontal) -> Option<Self> {
+        Some(match (first, second) {
+            (Horizontal::Top, Horizontal::Left) | (Horizontal::Left, Horizontal::Top) => {
+                Self::TopLeft
+            }
+            (Horizontal::Top, Horizontal::Right) | (Horizontal::Right, Horizontal::Top) => {
+                Self::TopRight
+            }
+            (Horizontal::Bottom, Horizontal::Left) | (Horizontal::Left, Horizontal::Bottom) => {
+                Self::BottomLeft
+            }
+            (Horizontal::Bottom, Horizontal::Right) | (Horizontal::Right, Horizontal::Bottom) => {
+                Self::BottomRight
+            }
+            _ => return None,
+        })
+    }
+}
+
+/// One of the eight directions of a square grid, either straight or diagonal.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum Direction {
+    Straight(Horizontal),
+    Diagonal(Diagonal),
+}
+
+impl Direction {
+    /// All eight directions, clockwise starting at the top.
+    pub const ALL: [Self; 8] = [
+        Self::Straight(Horizontal::Top),
+        Self::Diagonal(Diagonal::TopRight),
+        Self::Straight(Horizontal::Right),
+        Self::Diagonal(Diagonal::BottomRight),
+        Self::Straight(Horizontal::Bottom),
+        Self::Diagonal(Diagonal::BottomLeft),
+        Self::Straight(Horizontal::Left),
+        Self::Diagonal(Diagonal::TopLeft),
+    ];
+
+    /// The four straight directions, in the same order as [`Horizontal::ALL`].
+    pub const STRAIGHT: [Self; 4] = [
+        Self::Straight(Horizontal::Top),
+        Self::Straight(Horizontal::Left),
+        Self::Straight(Horizontal::Right),
+        Self::Straight(Horizontal::Bottom),
+    ];
+
+    #[must_use]
+    pub const fn to_offset(self) -> Offset {
+        match self {
+            Self::Straight(horizontal) => horizontal.to_offset(),
+            Self::Diagonal(diagonal) => diagonal.to_offset(),
+        }
+    }
+
+    #[must_use]
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::Straight(horizontal) => Self::Straight(horizontal.opposite()),
+            Self::Diagonal(diagonal) => Self::Diagonal(diagonal.opposite()),
+        }
+    }
+}
+
+impl From<Horizontal> for Direction {
+    fn from(value: Horizontal) -> Self {
+        Self::Straight(value)
+    }
+}
+
+impl From<Diagonal> for Direction {
+    fn from(value: Diagonal) -> Self {
+        Self::Diagonal(value)
+    }
+}
+
+/// The six directions of a hex grid with pointy-top hexagons, in clockwise order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize)]
+pub enum HexDirection {
+    TopRight,
+    Right,
+    BottomRight,
+    BottomLeft,
+    Left,
+    TopLeft,
+}
+
+impl HexDirection {
+    pub const ALL: [Self; 6] = [
+        Self::TopRight,
+        Self::Right,
+        Self::BottomRight,
+        Self::BottomLeft,
+        Self::Left,
+        Self::TopLeft,
+    ];
+
+    /// The offset in axial coordinates (`x` = q, `y` = r).
+    #[must_use]
+    pub const fn to_offset(self) -> IVec2 {
+        match self {
+            Self::TopRight => IVec2::new(1, -1),
+            Self::Right => IVec2::new(1, 0),
+            Self::BottomRight => IVec2::new(0, 1),
+            Self::BottomLeft => IVec2::new(-1, 1),
+            Self::Left => IVec2::new(-1, 0),
+            Self::TopLeft => IVec2::new(0, -1),
+        }
+    }
+
+    /// The hex direction which points the same way as `direction`.
+    /// Hex grids with pointy-top hexagons have no straight way up or down.
+    #[must_use]
+    pub const fn from_horizontal(direction: Horizontal) -> Option<Self> {
+        match direction {
+            Horizontal::Left => Some(Self::Left),
+            Horizontal::Right => Some(Self::Right),
+            Horizontal::Top | Horizontal::Bottom => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::TopRight => Self::BottomLeft,
+            Self::Right => Self::Left,
+            Self::BottomRight => Self::TopLeft,
+            Self::BottomLeft => Self::TopRight,
+            Self::Left => Self::Right,
+            Self::TopLeft => Self::BottomRight,
+        }
+    }
 }
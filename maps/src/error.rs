@@ -0,0 +1,34 @@
+use crate::prelude::Pos;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum MapParseError {
+    #[error("invalid tile \"{glyph}\" at row {row}, column {column}: {message}")]
+    InvalidTile {
+        row: usize,
+        column: usize,
+        glyph: String,
+        message: String,
+    },
+
+    #[error("row {row} has {found} tiles, but the first row has {expected}")]
+    RowLength {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("portal {portal} needs exactly one partner, but appears {count} times")]
+    UnpairedPortal { portal: char, count: usize },
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum MapPatchError {
+    #[error("the patch is for a map of size {expected}, but the map has size {found}")]
+    SizeMismatch { expected: Pos, found: Pos },
+
+    #[error("the change at {pos} is outside of the map")]
+    OutOfBounds { pos: Pos },
+
+    #[error("the cell at {pos} doesn't hold the old value of the change")]
+    Mismatch { pos: Pos },
+}
@@ -0,0 +1,98 @@
+use std::{
+    fmt::{Display, Write},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{Horizontal, LevelGlyph};
+
+/// The ground of a tile, which lies below the objects of the [`RockKind`](crate::prelude::RockKind) layer.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, strum::VariantNames,
+)]
+pub enum Floor {
+    #[default]
+    Normal,
+    /// A rock has to rest on every target to win
+    Target,
+    /// Rocks can't stop on ice, so it doesn't count towards the `max_roll_distance`,
+    /// rocks pushed onto it keep sliding and they slide past the closed sides of reflectors
+    Ice,
+    /// Stops every rock that rolls onto it
+    Sand,
+    /// Swallows every rock that rolls onto it
+    Pit,
+    /// Swallows the first rock that rolls onto it and is normal floor afterwards
+    ShallowPit,
+    /// Pushes resting rocks one tile in its direction after every tilt
+    Conveyor(Horizontal),
+    /// Opens or closes its linked doors while a rock rests on it
+    PressurePlate,
+    /// Opens or closes its linked doors every time a rock rolls over it
+    Switch,
+}
+
+impl FromStr for Floor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "." => Self::Normal,
+            "+" => Self::Target,
+            "~" => Self::Ice,
+            ":" => Self::Sand,
+            "x" => Self::Pit,
+            "u" => Self::ShallowPit,
+            "=" => Self::PressurePlate,
+            "*" => Self::Switch,
+            "⇑" => Self::Conveyor(Horizontal::Top),
+            "⇐" => Self::Conveyor(Horizontal::Left),
+            "⇒" => Self::Conveyor(Horizontal::Right),
+            "⇓" => Self::Conveyor(Horizontal::Bottom),
+            _ => return Err(format!("{s} is not a Floor")),
+        })
+    }
+}
+
+impl LevelGlyph for Floor {
+    fn write_level_glyph(&self, f: &mut impl Write) -> std::fmt::Result {
+        match self {
+            Self::Normal => f.write_char('.'),
+            Self::Target => f.write_char('+'),
+            Self::Ice => f.write_char('~'),
+            Self::Sand => f.write_char(':'),
+            Self::Pit => f.write_char('x'),
+            Self::ShallowPit => f.write_char('u'),
+            Self::PressurePlate => f.write_char('='),
+            Self::Switch => f.write_char('*'),
+            Self::Conveyor(direction) => f.write_char(match direction {
+                Horizontal::Top => '⇑',
+                Horizontal::Left => '⇐',
+                Horizontal::Right => '⇒',
+                Horizontal::Bottom => '⇓',
+            }),
+        }
+    }
+}
+
+impl Display for Floor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Normal => f.write_char('•'),
+            Self::Target => f.write_char('◎'),
+            Self::Ice => f.write_char('≈'),
+            Self::Sand => f.write_char('∷'),
+            Self::Pit => f.write_char('◌'),
+            Self::ShallowPit => f.write_char('◡'),
+            Self::PressurePlate => f.write_char('▭'),
+            Self::Switch => f.write_char('◇'),
+            Self::Conveyor(direction) => f.write_char(match direction {
+                Horizontal::Top => '⇑',
+                Horizontal::Left => '⇐',
+                Horizontal::Right => '⇒',
+                Horizontal::Bottom => '⇓',
+            }),
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use std::fmt::Write;
+
+/// The glyph of a void cell in the level text format, which is outside of the playfield.
+pub const VOID_GLYPH: &str = "_";
+
+/// The glyph used for a value in the level text format, which `FromStr` reads back.
+///
+/// This is separate from `Display`, which renders the prettier in-game glyphs.
+pub trait LevelGlyph {
+    /// # Errors
+    ///
+    /// This function will return an error if writing to `f` fails.
+    fn write_level_glyph(&self, f: &mut impl Write) -> std::fmt::Result;
+
+    #[must_use]
+    fn level_glyph(&self) -> String {
+        let mut glyph = String::new();
+        self.write_level_glyph(&mut glyph)
+            .expect("writing to a String never fails");
+        glyph
+    }
+}
+
+impl LevelGlyph for char {
+    fn write_level_glyph(&self, f: &mut impl Write) -> std::fmt::Result {
+        f.write_char(*self)
+    }
+}
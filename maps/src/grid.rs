@@ -0,0 +1,173 @@
+use std::{collections::HashSet, fmt::Debug, hash::Hash};
+
+use crate::prelude::{Axial, HexDirection, HexMap, Horizontal, Map, Pos, Regions};
+
+/// The shape of a grid, which lets algorithms like tilting walk over square and hex maps alike.
+pub trait Grid {
+    type Pos: Copy + Eq + Hash + Debug;
+    type Direction: Copy + Eq + Debug + 'static;
+    type Item;
+
+    /// The directions in which a position can have neighbours.
+    const DIRECTIONS: &'static [Self::Direction];
+
+    /// The width and height of the cells in which the grid is stored, including void cells.
+    fn size(&self) -> Pos;
+
+    /// The item at `pos`, or `None` if `pos` is not part of the grid.
+    fn get(&self, pos: &Self::Pos) -> Option<&Self::Item>;
+
+    /// The neighbouring position in `direction`, if it is part of the grid.
+    fn neighbor(&self, pos: &Self::Pos, direction: Self::Direction) -> Option<Self::Pos>;
+
+    /// All positions which are part of the grid, row by row.
+    fn positions(&self) -> impl Iterator<Item = Self::Pos> + '_;
+
+    /// All positions which are part of the grid together with their items, row by row.
+    fn cells(&self) -> impl Iterator<Item = (Self::Pos, &Self::Item)> + '_ {
+        self.positions()
+            .filter_map(|pos| Some((pos, self.get(&pos)?)))
+    }
+
+    /// All positions which can be reached from `start` by going from neighbour to neighbour over items matching `passable`.
+    ///
+    /// The result is empty if `start` itself isn't passable.
+    fn flood_fill(
+        &self,
+        start: &Self::Pos,
+        mut passable: impl FnMut(&Self::Item) -> bool,
+    ) -> HashSet<Self::Pos> {
+        flood_fill_with(self, *start, &mut passable)
+    }
+
+    /// Splits the positions with items matching `passable` into connected regions.
+    /// The regions are labelled in the order of [`Grid::positions`] of their first position, starting at `0`.
+    fn regions(&self, mut passable: impl FnMut(&Self::Item) -> bool) -> Regions<Self::Pos> {
+        let mut regions = Regions::default();
+
+        for pos in self.positions() {
+            if regions.labels.contains_key(&pos) {
+                continue;
+            }
+
+            let region = flood_fill_with(self, pos, &mut passable);
+            if region.is_empty() {
+                continue;
+            }
+
+            let label = regions.regions.len();
+            regions
+                .labels
+                .extend(region.iter().map(|pos| (*pos, label)));
+            regions.regions.push(region);
+        }
+
+        regions
+    }
+}
+
+/// A [`Grid`] whose items can be changed.
+pub trait GridMut: Grid {
+    /// The item at `pos`, or `None` if `pos` is not part of the grid.
+    fn get_mut(&mut self, pos: &Self::Pos) -> Option<&mut Self::Item>;
+
+    /// Replaces the item at `pos` and returns the old one.
+    /// Returns `None` and changes nothing if `pos` is not part of the grid.
+    fn set(&mut self, pos: &Self::Pos, item: Self::Item) -> Option<Self::Item> {
+        self.get_mut(pos).map(|cell| std::mem::replace(cell, item))
+    }
+}
+
+fn flood_fill_with<G: Grid + ?Sized>(
+    grid: &G,
+    start: G::Pos,
+    passable: &mut impl FnMut(&G::Item) -> bool,
+) -> HashSet<G::Pos> {
+    let mut filled = HashSet::new();
+
+    if !grid.get(&start).is_some_and(&mut *passable) {
+        return filled;
+    }
+
+    let mut queue = vec![start];
+    filled.insert(start);
+
+    while let Some(pos) = queue.pop() {
+        for direction in G::DIRECTIONS {
+            let Some(neighbor) = grid.neighbor(&pos, *direction) else {
+                continue;
+            };
+
+            if !filled.contains(&neighbor) && grid.get(&neighbor).is_some_and(&mut *passable) {
+                filled.insert(neighbor);
+                queue.push(neighbor);
+            }
+        }
+    }
+
+    filled
+}
+
+impl<T> Grid for Map<T> {
+    type Pos = Pos;
+    type Direction = Horizontal;
+    type Item = T;
+
+    const DIRECTIONS: &'static [Horizontal] = &Horizontal::ALL;
+
+    fn size(&self) -> Pos {
+        Pos::new(self.width(), self.height())
+    }
+
+    fn get(&self, pos: &Pos) -> Option<&T> {
+        Self::get(self, pos)
+    }
+
+    fn neighbor(&self, pos: &Pos, direction: Horizontal) -> Option<Pos> {
+        Self::neighbor(self, pos, direction)
+    }
+
+    fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.all_pos()
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.iter()
+    }
+}
+
+impl<T> GridMut for Map<T> {
+    fn get_mut(&mut self, pos: &Pos) -> Option<&mut T> {
+        Self::get_mut(self, pos)
+    }
+}
+
+impl<T> Grid for HexMap<T> {
+    type Pos = Axial;
+    type Direction = HexDirection;
+    type Item = T;
+
+    const DIRECTIONS: &'static [HexDirection] = &HexDirection::ALL;
+
+    fn size(&self) -> Pos {
+        self.offset_map().size()
+    }
+
+    fn get(&self, pos: &Axial) -> Option<&T> {
+        Self::get(self, pos)
+    }
+
+    fn neighbor(&self, pos: &Axial, direction: HexDirection) -> Option<Axial> {
+        Self::neighbor(self, pos, direction)
+    }
+
+    fn positions(&self) -> impl Iterator<Item = Axial> + '_ {
+        self.all_axial()
+    }
+}
+
+impl<T> GridMut for HexMap<T> {
+    fn get_mut(&mut self, pos: &Axial) -> Option<&mut T> {
+        Self::get_mut(self, pos)
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+use crate::prelude::{Axial, HexDirection, Map, MapParseError, Pos, Tile};
+
+/// The three axes of a hex grid. Each one has lines of cells, like the rows and columns of a [`Map`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize)]
+pub enum HexAxis {
+    /// Lines with the same r, going [`HexDirection::Right`]
+    R,
+    /// Lines with the same q, going [`HexDirection::BottomRight`]
+    Q,
+    /// Lines with the same s (`-q - r`), going [`HexDirection::BottomLeft`]
+    S,
+}
+
+impl HexAxis {
+    #[must_use]
+    pub const fn direction(self) -> HexDirection {
+        match self {
+            Self::R => HexDirection::Right,
+            Self::Q => HexDirection::BottomRight,
+            Self::S => HexDirection::BottomLeft,
+        }
+    }
+}
+
+/// Converts an offset position ("odd-r" layout, every odd row is shifted half a cell to the right)
+/// to axial coordinates.
+#[must_use]
+#[allow(clippy::cast_possible_wrap)]
+pub const fn offset_to_axial(pos: Pos) -> Axial {
+    let row = pos.y as i32;
+    Axial::new(pos.x as i32 - (row - (row & 1)) / 2, row)
+}
+
+/// Converts axial coordinates to an offset position ("odd-r" layout).
+/// Returns `None` if the position would be negative.
+#[must_use]
+#[allow(clippy::cast_sign_loss)]
+pub const fn axial_to_offset(axial: Axial) -> Option<Pos> {
+    let col = axial.x + (axial.y - (axial.y & 1)) / 2;
+
+    if col < 0 || axial.y < 0 {
+        return None;
+    }
+
+    Some(Pos::new(col as u32, axial.y as u32))
+}
+
+/// A hex grid addressed with [`Axial`] coordinates.
+///
+/// The cells are stored in a [`Map`] in the "odd-r" offset layout, so the text format is the same
+/// as for square maps, where every odd row is meant to be shifted half a cell to the right.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexMap<T = Tile> {
+    cells: Map<T>,
+}
+
+impl<T> From<Map<T>> for HexMap<T> {
+    fn from(cells: Map<T>) -> Self {
+        Self { cells }
+    }
+}
+
+impl<T: FromStr> FromStr for HexMap<T>
+where
+    <T as FromStr>::Err: Display,
+{
+    type Err = MapParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Map::from_str(s).map(Self::from)
+    }
+}
+
+impl<T> HexMap<T> {
+    /// The cells in the offset layout.
+    #[must_use]
+    pub const fn offset_map(&self) -> &Map<T> {
+        &self.cells
+    }
+
+    #[must_use]
+    pub fn get(&self, axial: &Axial) -> Option<&T> {
+        self.cells.get(&axial_to_offset(*axial)?)
+    }
+
+    pub fn get_mut(&mut self, axial: &Axial) -> Option<&mut T> {
+        self.cells.get_mut(&axial_to_offset(*axial)?)
+    }
+
+    #[must_use]
+    pub fn contains(&self, axial: &Axial) -> bool {
+        self.get(axial).is_some()
+    }
+
+    /// The neighbouring cell in `direction`, if it is part of the map.
+    #[must_use]
+    pub fn neighbor(&self, axial: &Axial, direction: HexDirection) -> Option<Axial> {
+        let neighbor = *axial + direction.to_offset();
+        self.contains(&neighbor).then_some(neighbor)
+    }
+
+    /// All non-void cells in axial coordinates, row by row.
+    pub fn all_axial(&self) -> impl Iterator<Item = Axial> + '_ {
+        self.cells.all_pos().map(offset_to_axial)
+    }
+
+    /// Walks from `start` in `direction` until the edge of the map.
+    #[must_use]
+    pub const fn line_iter(&self, start: Axial, direction: HexDirection) -> HexLineIter<'_, T> {
+        HexLineIter {
+            map: self,
+            next: Some(start),
+            direction,
+        }
+    }
+
+    /// All lines along `axis`, like [`Map::rows`] and [`Map::columns`] for square maps.
+    /// A line is interrupted by void cells, and each part is its own line.
+    pub fn lines(&self, axis: HexAxis) -> impl Iterator<Item = HexLineIter<'_, T>> {
+        let direction = axis.direction();
+
+        self.all_axial()
+            .filter(move |axial| self.neighbor(axial, direction.opposite()).is_none())
+            .map(move |start| self.line_iter(start, direction))
+    }
+}
+
+pub struct HexLineIter<'a, T> {
+    map: &'a HexMap<T>,
+    next: Option<Axial>,
+    direction: HexDirection,
+}
+
+impl<'a, T> Iterator for HexLineIter<'a, T> {
+    type Item = (Axial, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        let item = self.map.get(&current)?;
+
+        self.next = self.map.neighbor(&current, self.direction);
+
+        Some((current, item))
+    }
+}
+
+#[cfg(test)]
+mod hex_tests {
+    use std::collections::HashSet;
+
+    use crate::prelude::Grid;
+
+    use super::*;
+
+    fn get_test_hex_map() -> HexMap<char> {
+        HexMap::from_str(
+            "\
+a b c
+ d e f
+g h i",
+        )
+        .expect("Map should parse")
+    }
+
+    #[test]
+    fn offset_conversion() {
+        for pos in get_test_hex_map().offset_map().all_pos() {
+            assert_eq!(axial_to_offset(offset_to_axial(pos)), Some(pos));
+        }
+
+        assert_eq!(offset_to_axial(Pos::new(0, 2)), Axial::new(-1, 2));
+        assert_eq!(axial_to_offset(Axial::new(-1, 1)), None);
+    }
+
+    #[test]
+    fn neighbors() {
+        let map = get_test_hex_map();
+        let e = offset_to_axial(Pos::new(1, 1));
+
+        assert_eq!(map.get(&e), Some(&'e'));
+        assert_eq!(
+            HexDirection::ALL
+                .map(|direction| map.neighbor(&e, direction).and_then(|n| map.get(&n))),
+            [
+                Some(&'c'),
+                Some(&'f'),
+                Some(&'i'),
+                Some(&'h'),
+                Some(&'d'),
+                Some(&'b')
+            ]
+        );
+    }
+
+    #[test]
+    fn lines_along_axes() {
+        let map = get_test_hex_map();
+        let lines = |axis| {
+            map.lines(axis)
+                .map(|line| line.map(|(_, item)| item).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(lines(HexAxis::R), vec!["abc", "def", "ghi"]);
+        assert_eq!(lines(HexAxis::Q), vec!["adh", "bei", "cf", "g"]);
+        assert_eq!(lines(HexAxis::S), vec!["a", "bdg", "ceh", "fi"]);
+    }
+
+    #[test]
+    fn flood_fill_follows_hex_neighbors() {
+        let map = get_test_hex_map();
+
+        let filled = map.flood_fill(&Axial::new(0, 0), |item| "adh".contains(*item));
+
+        assert_eq!(
+            filled
+                .iter()
+                .filter_map(|axial| map.get(axial))
+                .collect::<HashSet<_>>(),
+            HashSet::from([&'a', &'d', &'h'])
+        );
+        assert_eq!(map.size(), Pos::new(3, 3));
+    }
+}
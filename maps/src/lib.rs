@@ -1,9 +1,18 @@
 pub mod prelude;
 
 pub(crate) mod direction;
+pub(crate) mod error;
+pub(crate) mod floor;
+pub(crate) mod glyph;
+pub(crate) mod grid;
+pub(crate) mod hex;
 pub(crate) mod map;
+pub(crate) mod portal;
 pub(crate) mod pos;
+pub(crate) mod region;
 pub(crate) mod rock;
 pub(crate) mod tile;
+pub(crate) mod transform;
+pub(crate) mod wrap;
 
 pub struct W<T>(pub T);
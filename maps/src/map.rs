@@ -1,252 +1,469 @@
+pub mod cell_iter;
 pub mod column_iter;
+pub mod diff;
+pub mod formats;
+pub mod query;
 pub mod row_iter;
+pub mod view;
 
 use std::{
-    cmp::Ordering, collections::HashMap, convert::Infallible, fmt::Debug, ops::Index, str::FromStr,
+    fmt::{Debug, Display},
+    ops::{Index, IndexMut, Range},
+    str::FromStr,
 };
 
-use bevy_math::{URect, UVec2};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
 
-use crate::prelude::{Pos, Tile};
+use crate::prelude::{LevelGlyph, MapParseError, Pos, Tile, Transform, Transformable, VOID_GLYPH};
 
 use self::{
     column_iter::{ColumnIter, ColumnsIter},
     row_iter::{RowIter, RowsIter},
 };
 
+/// A grid of `T`, stored row by row in one contiguous `Vec`.
+///
+/// Cells outside of the playfield are void (`None`), so maps don't have to be rectangular.
+/// Void cells are skipped by the iterators and behave like positions outside of the map.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
-#[serde(from = "&str")]
-pub struct Map<T: FromStr + Debug = Tile>
-where
-    <T as FromStr>::Err: Debug,
-{
-    pub rect: URect,
-    pub items: HashMap<Pos, T>,
+#[serde(
+    try_from = "&str",
+    bound(deserialize = "T: FromStr, <T as FromStr>::Err: Display")
+)]
+pub struct Map<T = Tile> {
+    width: u32,
+    height: u32,
+    items: Vec<Option<T>>,
 }
 
-impl<T: FromStr + Debug> Index<&Pos> for Map<T>
-where
-    <T as FromStr>::Err: Debug,
-{
+impl<T> Index<Pos> for Map<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, pos: Pos) -> &Self::Output {
+        &self[&pos]
+    }
+}
+
+impl<T> Index<&Pos> for Map<T> {
     type Output = T;
 
     #[inline]
     fn index(&self, pos: &Pos) -> &Self::Output {
-        &self.items[pos]
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos} is outside of the map or void"))
     }
 }
 
-impl<T: FromStr + Debug> Map<T>
-where
-    <T as FromStr>::Err: Debug,
-{
+impl<T> IndexMut<Pos> for Map<T> {
+    #[inline]
+    fn index_mut(&mut self, pos: Pos) -> &mut Self::Output {
+        &mut self[&pos]
+    }
+}
+
+impl<T> IndexMut<&Pos> for Map<T> {
+    #[inline]
+    fn index_mut(&mut self, pos: &Pos) -> &mut Self::Output {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos} is outside of the map or void"))
+    }
+}
+
+impl<T> Map<T> {
     /// # Panics
     ///
-    /// Panics if the map is too big.
+    /// Panics if the map is too big or the rows don't all have the same length.
     pub fn new(items: impl IntoIterator<Item = impl IntoIterator<Item = T>>) -> Self {
-        let items: HashMap<_, _> = items
-            .into_iter()
-            .enumerate()
-            .flat_map(|row| {
-                row.1.into_iter().enumerate().map(move |(x, item)| {
-                    (
-                        UVec2::new(
-                            u32::try_from(x).expect("Map is too big"),
-                            u32::try_from(row.0).expect("Map is too big"),
-                        ),
-                        item,
-                    )
-                })
-            })
-            .collect();
+        Self::from_cells(items.into_iter().map(|row| row.into_iter().map(Some)))
+    }
 
-        Self {
-            rect: URect::from_corners(
-                UVec2::ZERO,
-                UVec2::new(
-                    items.keys().map(|pos| pos.x).max().unwrap_or(0),
-                    items.keys().map(|pos| pos.y).max().unwrap_or(0),
-                ),
-            ),
-            items,
+    /// Creates a map from rows of cells, where `None` is a void cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the map is too big or the rows don't all have the same length.
+    pub fn from_cells(
+        items: impl IntoIterator<Item = impl IntoIterator<Item = Option<T>>>,
+    ) -> Self {
+        Self::try_from_cells(items).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Like [`Map::from_cells`], but returns an error if a row doesn't have as many cells as the first one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MapParseError::RowLength`] with the 1-based index of the first row with a different length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the map is too big.
+    pub fn try_from_cells(
+        items: impl IntoIterator<Item = impl IntoIterator<Item = Option<T>>>,
+    ) -> Result<Self, MapParseError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut all_items = Vec::new();
+
+        for (index, row) in items.into_iter().enumerate() {
+            let len_before = all_items.len();
+            all_items.extend(row);
+            let row_width = all_items.len() - len_before;
+
+            let expected = *width.get_or_insert(row_width);
+            if expected != row_width {
+                return Err(MapParseError::RowLength {
+                    row: index + 1,
+                    expected,
+                    found: row_width,
+                });
+            }
+            height += 1;
         }
+
+        Ok(Self {
+            width: u32::try_from(width.unwrap_or(0)).expect("Map is too big"),
+            height: u32::try_from(height).expect("Map is too big"),
+            items: all_items,
+        })
     }
 
     #[must_use]
     pub const fn width(&self) -> u32 {
-        self.rect.width() + 1
+        self.width
     }
 
     #[must_use]
     pub const fn height(&self) -> u32 {
-        self.rect.height() + 1
+        self.height
+    }
+
+    /// Whether the position is inside the bounds of the map, which includes void cells.
+    #[must_use]
+    pub const fn contains(&self, pos: &Pos) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    /// Whether the position is a non-void cell of the map.
+    #[must_use]
+    pub fn has_cell(&self, pos: &Pos) -> bool {
+        self.get(pos).is_some()
+    }
+
+    #[must_use]
+    pub fn is_void(&self, pos: &Pos) -> bool {
+        matches!(self.get_cell(pos), Some(None))
+    }
+
+    const fn index_of(&self, pos: Pos) -> Option<usize> {
+        if self.contains(&pos) {
+            Some(pos.y as usize * self.width as usize + pos.x as usize)
+        } else {
+            None
+        }
     }
 
     #[must_use]
     pub fn get(&self, pos: &Pos) -> Option<&T> {
-        self.items.get(pos)
+        self.items.get(self.index_of(*pos)?)?.as_ref()
     }
 
     pub fn get_mut(&mut self, pos: &Pos) -> Option<&mut T> {
-        self.items.get_mut(pos)
+        let index = self.index_of(*pos)?;
+        self.items.get_mut(index)?.as_mut()
     }
 
+    /// Returns the cell at the position, which is `None` if it is void.
+    /// Returns `None` if the position is outside of the map.
     #[must_use]
-    pub const fn rows(&self) -> RowsIter<T> {
+    pub fn get_cell(&self, pos: &Pos) -> Option<&Option<T>> {
+        self.items.get(self.index_of(*pos)?)
+    }
+
+    /// Swaps the values at both positions. Does nothing if one of them is outside of the map or void.
+    pub fn swap(&mut self, pos1: &Pos, pos2: &Pos) {
+        let (Some(index1), Some(index2)) = (self.index_of(*pos1), self.index_of(*pos2)) else {
+            return;
+        };
+
+        if self.items[index1].is_some() && self.items[index2].is_some() {
+            self.items.swap(index1, index2);
+        }
+    }
+
+    /// The cells of a row including void cells, so the index in the slice is the x position.
+    #[must_use]
+    pub fn row(&self, row: u32) -> Option<&[Option<T>]> {
+        self.items.get(self.row_range(row)?)
+    }
+
+    pub fn row_mut(&mut self, row: u32) -> Option<&mut [Option<T>]> {
+        let range = self.row_range(row)?;
+        self.items.get_mut(range)
+    }
+
+    /// The indices of the cells of a row, which is empty for a map without columns.
+    const fn row_range(&self, row: u32) -> Option<Range<usize>> {
+        if row >= self.height {
+            return None;
+        }
+
+        let start = row as usize * self.width as usize;
+        Some(start..start + self.width as usize)
+    }
+
+    #[must_use]
+    pub const fn rows(&self) -> RowsIter<'_, T> {
         RowsIter::new(self)
     }
 
     #[must_use]
-    pub const fn row_iter(&self, row: u32) -> RowIter<T> {
+    pub fn row_iter(&self, row: u32) -> RowIter<'_, T> {
         RowIter::new(self, row)
     }
 
     #[must_use]
-    pub const fn columns(&self) -> ColumnsIter<T> {
+    pub const fn columns(&self) -> ColumnsIter<'_, T> {
         ColumnsIter::new(self)
     }
 
     #[must_use]
-    pub const fn column_iter(&self, col: u32) -> ColumnIter<T> {
+    pub fn column_iter(&self, col: u32) -> ColumnIter<'_, T> {
         ColumnIter::new(self, col)
     }
 
-    pub fn all_pos(&self) -> impl Iterator<Item = &Pos> {
-        self.items.keys()
+    /// All non-void positions of the map in row-major order.
+    #[must_use]
+    pub const fn all_pos(&self) -> AllPosIter<'_, T> {
+        AllPosIter(self, 0)
     }
 
-    pub fn all_pos_ordered(&self) -> impl Iterator<Item = &Pos> {
-        let mut pos: Vec<_> = self.all_pos().collect();
-        pos.sort_by(|pos, next| match pos.y.cmp(&next.y) {
-            Ordering::Equal => pos.x.cmp(&next.x),
-            result => result,
-        });
-        pos.into_iter()
+    #[must_use]
+    pub const fn all_pos_iter(&self) -> AllPosIter<'_, T> {
+        self.all_pos()
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn pos_of_index(width: u32, index: usize) -> Pos {
+    Pos::new(
+        (index % width as usize) as u32,
+        (index / width as usize) as u32,
+    )
+}
+
+pub struct AllPosIter<'a, T>(&'a Map<T>, usize);
+
+impl<T> Iterator for AllPosIter<'_, T> {
+    type Item = Pos;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let cell = self.0.items.get(self.1)?;
+            let pos = pos_of_index(self.0.width, self.1);
+            self.1 += 1;
+
+            if cell.is_some() {
+                return Some(pos);
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.0.items.len().saturating_sub(self.1)))
     }
+}
 
+impl<T: Default + Clone> Map<T> {
     #[must_use]
-    pub const fn all_pos_iter(&self) -> AllPosIter<T> {
-        AllPosIter(self, None)
+    pub fn with_size(x: u32, y: u32) -> Self {
+        Self {
+            width: x,
+            height: y,
+            items: vec![Some(T::default()); x as usize * y as usize],
+        }
     }
 }
 
-impl<T: Clone + FromStr + Debug> Map<T>
-where
-    <T as FromStr>::Err: Debug,
-{
-    #[allow(clippy::missing_panics_doc)]
-    pub fn swap(&mut self, pos1: &Pos, pos2: &Pos) {
-        let Some(val1) = self.get(pos1).cloned() else {
-            return;
-        };
+impl<T: Transformable> Map<T> {
+    /// Moves every cell to its transformed position and transforms the cell itself,
+    /// so e.g. reflectors keep pointing the right way.
+    #[must_use]
+    pub fn transform(self, transform: Transform) -> Self {
+        let (width, height) = transform.map_size(self.width, self.height);
 
-        let Some(val2) = self.get(pos2).cloned() else {
-            return;
-        };
+        let mut items = std::iter::repeat_with(|| None)
+            .take(self.items.len())
+            .collect::<Vec<_>>();
 
-        *self
-            .get_mut(pos1)
-            .expect("pos1 exitists because of the `get` above") = val2;
+        for (index, item) in self.items.into_iter().enumerate() {
+            let pos = pos_of_index(self.width, index);
+            let new_pos = transform.map_pos(pos, self.width, self.height);
+            items[new_pos.y as usize * width as usize + new_pos.x as usize] =
+                item.transformed(transform);
+        }
 
-        *self
-            .get_mut(pos2)
-            .expect("pos1 exitists because of the `get` above") = val1;
+        Self {
+            width,
+            height,
+            items,
+        }
     }
-}
 
-pub struct AllPosIter<'a, T: FromStr + Debug>(&'a Map<T>, Option<Pos>)
-where
-    <T as FromStr>::Err: Debug;
+    #[must_use]
+    pub fn rotate_cw(self) -> Self {
+        self.transform(Transform::RotateCw)
+    }
 
-impl<'a, T: FromStr + Debug> Iterator for AllPosIter<'a, T>
+    #[must_use]
+    pub fn rotate_ccw(self) -> Self {
+        self.transform(Transform::RotateCcw)
+    }
+
+    #[must_use]
+    pub fn flip_horizontal(self) -> Self {
+        self.transform(Transform::FlipHorizontal)
+    }
+
+    #[must_use]
+    pub fn flip_vertical(self) -> Self {
+        self.transform(Transform::FlipVertical)
+    }
+
+    #[must_use]
+    pub fn transpose(self) -> Self {
+        self.transform(Transform::Transpose)
+    }
+}
+
+impl<T: FromStr> FromStr for Map<T>
 where
-    <T as FromStr>::Err: Debug,
+    <T as FromStr>::Err: Display,
 {
-    type Item = Pos;
+    type Err = MapParseError;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match &mut self.1 {
-            Some(current_pos) => {
-                current_pos.x += 1;
-                if current_pos.x == self.0.width() {
-                    current_pos.x = 0;
-                    current_pos.y += 1;
-
-                    if current_pos.y == self.0.height() {
-                        return None;
+    /// Parses a map from whitespace separated fields, one row per line. Blank lines are skipped.
+    ///
+    /// A [`VOID_GLYPH`] field is a void cell, so every row needs a field for each column.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rows = Vec::<Vec<Option<T>>>::new();
+
+        for (line_index, line) in s.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let row = split_fields(line)
+                .map(|(column, glyph)| {
+                    if glyph == VOID_GLYPH {
+                        return Ok(None);
                     }
+
+                    T::from_str(glyph)
+                        .map(Some)
+                        .map_err(|err| MapParseError::InvalidTile {
+                            row: line_index + 1,
+                            column,
+                            glyph: glyph.to_owned(),
+                            message: err.to_string(),
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if let Some(first_row) = rows.first() {
+                if first_row.len() != row.len() {
+                    return Err(MapParseError::RowLength {
+                        row: line_index + 1,
+                        expected: first_row.len(),
+                        found: row.len(),
+                    });
                 }
             }
-            None => self.1 = Some(Pos::default()),
-        };
 
-        self.1
+            rows.push(row);
+        }
+
+        Ok(Self::from_cells(rows))
     }
 }
 
-impl<T: Default + Clone + FromStr + Debug> Map<T>
+impl<T: FromStr> TryFrom<&str> for Map<T>
 where
-    <T as FromStr>::Err: Debug,
+    <T as FromStr>::Err: Display,
 {
-    #[must_use]
-    pub fn with_size(x: u32, y: u32) -> Self {
-        let row = (0..x).map(|_| T::default());
-        Self::new((0..y).map(|_| row.clone()))
+    type Error = MapParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
     }
 }
 
-impl<T: FromStr + Debug> FromStr for Map<T>
-where
-    Self: for<'a> From<&'a str>,
-    <T as FromStr>::Err: Debug,
-{
-    type Err = Infallible;
+impl<T: LevelGlyph> Map<T> {
+    /// Writes the map in the level text format, which can be parsed again with `FromStr`.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn to_level_string(&self) -> String {
+        let mut level = String::new();
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(s.into())
+        for row in self.rows() {
+            for (x, cell) in row.iter().enumerate() {
+                if x > 0 {
+                    level.push(' ');
+                }
+                match cell {
+                    Some(item) => item
+                        .write_level_glyph(&mut level)
+                        .expect("writing to a String never fails"),
+                    None => level.push_str(VOID_GLYPH),
+                }
+            }
+            level.push('\n');
+        }
+
+        level
     }
 }
 
-impl<T: FromStr + Debug> From<&str> for Map<T>
-where
-    <T as FromStr>::Err: Debug,
-{
-    fn from(value: &str) -> Self {
-        Self::new(value.lines().map(|line| {
-            line.split_whitespace()
-                .map(|field| T::from_str(field).expect("should parse field"))
-        }))
+impl<T: LevelGlyph> Serialize for Map<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_level_string())
     }
 }
 
+/// Splits a line at whitespace and returns every field together with its 1-based character column.
+fn split_fields(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace().map(move |field| {
+        let byte_offset = field.as_ptr() as usize - line.as_ptr() as usize;
+        (line[..byte_offset].chars().count() + 1, field)
+    })
+}
+
 #[cfg(test)]
 #[allow(clippy::module_name_repetitions)]
 pub fn get_test_map() -> Map<char> {
     Map::<char> {
-        rect: URect::from_corners(UVec2::ZERO, UVec2::new(2, 4)),
-        items: HashMap::from_iter([
-            (Pos::new(0, 0), '1'),
-            (Pos::new(1, 0), '2'),
-            (Pos::new(2, 0), '3'),
-            (Pos::new(0, 1), '4'),
-            (Pos::new(1, 1), '5'),
-            (Pos::new(2, 1), '6'),
-            (Pos::new(0, 2), '7'),
-            (Pos::new(1, 2), '8'),
-            (Pos::new(2, 2), '9'),
-            (Pos::new(0, 3), 'a'),
-            (Pos::new(1, 3), 'b'),
-            (Pos::new(2, 3), 'c'),
-            (Pos::new(0, 4), 'd'),
-            (Pos::new(1, 4), 'e'),
-            (Pos::new(2, 4), 'f'),
-        ]),
+        width: 3,
+        height: 5,
+        items: [
+            '1', '2', '3', //
+            '4', '5', '6', //
+            '7', '8', '9', //
+            'a', 'b', 'c', //
+            'd', 'e', 'f',
+        ]
+        .map(Some)
+        .to_vec(),
     }
 }
 
 #[cfg(test)]
 mod map_tests {
+    use std::collections::HashSet;
+
+    use crate::prelude::{
+        CellChange, Diagonal, Grid, GridMut, Horizontal, MapPatch, MapPatchError, RockKind, URect,
+    };
+
     use super::*;
 
     #[test]
@@ -260,7 +477,7 @@ a b c
 d e f
 ",
         )
-        .unwrap();
+        .expect("Map should parse");
 
         let expected = get_test_map();
 
@@ -279,12 +496,29 @@ d e f
         assert_eq!(map.get(&Pos { x: 2, y: 5 }), None);
     }
 
+    #[test]
+    fn index_map() {
+        let mut map = get_test_map();
+
+        assert_eq!(map[Pos::new(1, 3)], 'b');
+
+        map[Pos::new(1, 3)] = 'x';
+        assert_eq!(map[&Pos::new(1, 3)], 'x');
+
+        map.swap(&Pos::new(0, 0), &Pos::new(2, 4));
+        assert_eq!(map[Pos::new(0, 0)], 'f');
+        assert_eq!(map[Pos::new(2, 4)], '1');
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the map")]
+    fn index_outside_map() {
+        let _ = get_test_map()[Pos::new(3, 0)];
+    }
+
     #[test]
     fn get_all_pos_ordered() {
-        let map = get_test_map()
-            .all_pos_ordered()
-            .copied()
-            .collect::<Vec<_>>();
+        let map = get_test_map().all_pos().collect::<Vec<_>>();
 
         assert_eq!(
             map,
@@ -322,6 +556,32 @@ d e f
         assert_eq!(pos_iter.next(), None);
     }
 
+    #[test]
+    fn row_slices() {
+        let map = get_test_map();
+
+        assert_eq!(map.row(1), Some(&[Some('4'), Some('5'), Some('6')][..]));
+        assert_eq!(map.row(5), None);
+        assert_eq!(map.rows().count(), 5);
+        assert_eq!(
+            map.rows().next_back(),
+            Some(&[Some('d'), Some('e'), Some('f')][..])
+        );
+        assert_eq!(map.row_iter(2).collect::<String>(), "789");
+    }
+
+    #[test]
+    fn rows_of_a_map_without_columns() {
+        let map = Map::<char>::from_fn(0, 3, |_| unreachable!("the map has no cells"));
+
+        assert_eq!(map.height(), 3);
+        assert_eq!(map.rows().len(), 3);
+        assert!(map.rows().all(<[Option<char>]>::is_empty));
+        assert_eq!(map.row(2), Some(&[][..]));
+        assert_eq!(map.row(3), None);
+        assert_eq!(map.to_level_string(), "\n\n\n");
+    }
+
     #[test]
     fn column_iterator() {
         let map = &get_test_map();
@@ -336,5 +596,522 @@ d e f
 
         col_iter = map.column_iter(99);
         assert_eq!(col_iter.next(), None);
+
+        assert_eq!(
+            map.columns().map(Iterator::count).collect::<Vec<_>>(),
+            vec![5, 5, 5]
+        );
+    }
+
+    #[test]
+    fn parse_invalid_tile() {
+        let result = Map::<Tile>::from_str(
+            "
+            . o .
+            . # ?
+            ",
+        );
+
+        assert_eq!(
+            result,
+            Err(MapParseError::InvalidTile {
+                row: 3,
+                column: 17,
+                glyph: "?".to_owned(),
+                message: "? is not a RockKind".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn level_string_round_trip() {
+        let level = "\
+. o #
+◢ ◣ ◥
+◤ . .
+";
+        let map = Map::<Tile>::from_str(level).expect("Map should parse");
+
+        assert_eq!(
+            map.all_pos().map(|pos| map[pos].object).collect::<Vec<_>>(),
+            vec![
+                RockKind::Empty,
+                RockKind::RoundRock,
+                RockKind::SquareRock,
+                RockKind::SingleReflect(Diagonal::TopLeft),
+                RockKind::SingleReflect(Diagonal::TopRight),
+                RockKind::SingleReflect(Diagonal::BottomLeft),
+                RockKind::SingleReflect(Diagonal::BottomRight),
+                RockKind::Empty,
+                RockKind::Empty,
+            ]
+        );
+        assert_eq!(map.to_level_string(), level);
+        assert_eq!(Map::from_str(&map.to_level_string()), Ok(map));
+    }
+
+    #[test]
+    fn transform_map() {
+        let map = get_test_map();
+
+        assert_eq!(
+            map.clone().rotate_cw().to_level_string(),
+            "d a 7 4 1\ne b 8 5 2\nf c 9 6 3\n"
+        );
+        assert_eq!(
+            map.clone().rotate_ccw().to_level_string(),
+            "3 6 9 c f\n2 5 8 b e\n1 4 7 a d\n"
+        );
+        assert_eq!(
+            map.clone().flip_horizontal().to_level_string(),
+            "3 2 1\n6 5 4\n9 8 7\nc b a\nf e d\n"
+        );
+        assert_eq!(
+            map.clone().flip_vertical().to_level_string(),
+            "d e f\na b c\n7 8 9\n4 5 6\n1 2 3\n"
+        );
+        assert_eq!(
+            map.clone().transpose().to_level_string(),
+            "1 4 7 a d\n2 5 8 b e\n3 6 9 c f\n"
+        );
+
+        for pos in map.all_pos() {
+            assert_eq!(
+                map.clone().rotate_cw()[Transform::RotateCw.map_pos(pos, 3, 5)],
+                map[pos]
+            );
+        }
+    }
+
+    #[test]
+    fn rotate_reflector_tiles() {
+        let map = Map::<Tile>::from_str("◢ #\n. o").expect("Map should parse");
+
+        assert_eq!(map.rotate_cw().to_level_string(), ". ◣\no #\n");
+    }
+
+    #[test]
+    fn rotate_gate_tiles() {
+        let map = Map::<Tile>::from_str("→ ↑\n. o").expect("Map should parse");
+
+        assert_eq!(map.rotate_cw().to_level_string(), ". ↓\no →\n");
+
+        let map = Map::<Tile>::from_str("⇒").expect("Map should parse");
+
+        assert_eq!(map.rotate_cw().to_level_string(), "⇓\n");
+    }
+
+    #[test]
+    fn parse_ragged_rows() {
+        assert_eq!(
+            Map::<char>::try_from("1 2\n\n3"),
+            Err(MapParseError::RowLength {
+                row: 3,
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            Map::<char>::try_from_cells([vec![Some('1')], vec![Some('2'), None]]),
+            Err(MapParseError::RowLength {
+                row: 2,
+                expected: 1,
+                found: 2
+            })
+        );
+
+        let map = Map::<char>::try_from("1 2\n3 _").expect("Map should parse");
+        assert_eq!(map.get(&Pos::new(1, 1)), None);
+        assert!(map.contains(&Pos::new(1, 1)));
+        assert!(!map.has_cell(&Pos::new(1, 1)));
+        assert!(map.is_void(&Pos::new(1, 1)));
+        assert!(!map.is_void(&Pos::new(2, 1)));
+        assert_eq!(map.to_level_string(), "1 2\n3 _\n");
+    }
+
+    #[test]
+    fn void_cells_are_skipped() {
+        let map = Map::<char>::from_str(
+            "\
+1 2 3
+4 _ 6
+_ 8 _",
+        )
+        .expect("Map should parse");
+
+        assert_eq!(
+            map.all_pos().collect::<Vec<_>>(),
+            vec![
+                Pos::new(0, 0),
+                Pos::new(1, 0),
+                Pos::new(2, 0),
+                Pos::new(0, 1),
+                Pos::new(2, 1),
+                Pos::new(1, 2),
+            ]
+        );
+        assert_eq!(map.row_iter(1).collect::<String>(), "46");
+        assert_eq!(map.column_iter(1).collect::<String>(), "28");
+        assert_eq!(map.column_iter(2).collect::<String>(), "36");
+        assert_eq!(map.row(2), Some(&[None, Some('8'), None][..]));
+        assert_eq!(map.rotate_cw().to_level_string(), "_ 4 1\n8 _ 2\n_ 6 3\n");
+    }
+
+    #[test]
+    fn ray_until_edge_or_void() {
+        let map = get_test_map();
+
+        let ray = |start: Pos, direction| {
+            map.ray(&start, direction)
+                .map(|(_, item)| *item)
+                .collect::<String>()
+        };
+
+        assert_eq!(ray(Pos::new(1, 0), Horizontal::Bottom), "58be");
+        assert_eq!(ray(Pos::new(2, 2), Horizontal::Left), "87");
+        assert_eq!(ray(Pos::new(0, 0), Horizontal::Top), "");
+
+        let map = Map::<char>::from_str("a b c\nd _ f\n").expect("Map should parse");
+        assert_eq!(
+            map.ray(&Pos::new(1, 0), Horizontal::Bottom).count(),
+            0,
+            "void cells end the ray"
+        );
+    }
+
+    #[test]
+    fn first_blocking_cell() {
+        let map = get_test_map();
+
+        assert_eq!(
+            map.first_blocking(&Pos::new(0, 0), Horizontal::Bottom, |item| item
+                .is_alphabetic()),
+            Some((Pos::new(0, 3), &'a'))
+        );
+        assert_eq!(
+            map.first_blocking(&Pos::new(0, 3), Horizontal::Right, |item| item.is_numeric()),
+            None
+        );
+    }
+
+    #[test]
+    fn neighbors() {
+        let map = get_test_map();
+
+        let neighbors4 = |pos: Pos| {
+            map.neighbors4(&pos)
+                .map(|(_, item)| *item)
+                .collect::<String>()
+        };
+        let neighbors8 = |pos: Pos| {
+            map.neighbors8(&pos)
+                .map(|(_, item)| *item)
+                .collect::<String>()
+        };
+
+        assert_eq!(neighbors4(Pos::new(1, 1)), "2684");
+        assert_eq!(neighbors8(Pos::new(1, 1)), "23698741");
+        assert_eq!(neighbors4(Pos::new(0, 0)), "24");
+        assert_eq!(neighbors8(Pos::new(2, 4)), "ceb");
+    }
+
+    #[test]
+    fn cells_in_rect() {
+        let map = get_test_map();
+
+        let cells = |rect| {
+            map.cells_in_rect(rect)
+                .map(|(_, item)| *item)
+                .collect::<String>()
+        };
+
+        assert_eq!(cells(URect::new(1, 1, 3, 3)), "5689");
+        assert_eq!(cells(URect::new(2, 3, 10, 10)), "cf");
+        assert_eq!(cells(URect::new(5, 5, 10, 10)), "");
+        assert_eq!(
+            map.cells_in_rect(URect::new(0, 4, 1, 5))
+                .collect::<Vec<_>>(),
+            vec![(Pos::new(0, 4), &'d')]
+        );
+    }
+
+    #[test]
+    fn flood_fill() {
+        let map = Map::<char>::from_str(
+            "\
+. . # .
+# . # .
+. . # .
+# # . .
+",
+        )
+        .expect("Map should parse");
+
+        let filled = map.flood_fill(&Pos::new(1, 0), |item| *item == '.');
+        assert_eq!(
+            filled,
+            HashSet::from([
+                Pos::new(0, 0),
+                Pos::new(1, 0),
+                Pos::new(1, 1),
+                Pos::new(0, 2),
+                Pos::new(1, 2),
+            ])
+        );
+
+        assert!(map
+            .flood_fill(&Pos::new(2, 0), |item| *item == '.')
+            .is_empty());
+    }
+
+    #[test]
+    fn regions() {
+        let map = Map::<char>::from_str(
+            "\
+. . # .
+# . # .
+. . # _
+# # . .
+",
+        )
+        .expect("Map should parse");
+
+        let regions = map.regions(|item| *item == '.');
+
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions.label(&Pos::new(0, 0)), Some(0));
+        assert_eq!(regions.label(&Pos::new(0, 2)), Some(0));
+        assert_eq!(regions.label(&Pos::new(3, 0)), Some(1));
+        assert_eq!(regions.label(&Pos::new(2, 3)), Some(2));
+        assert_eq!(regions.label(&Pos::new(2, 0)), None);
+        assert_eq!(
+            regions.get(1),
+            Some(&HashSet::from([Pos::new(3, 0), Pos::new(3, 1)])),
+            "void cells separate regions"
+        );
+    }
+
+    #[test]
+    fn diff_and_patch() {
+        let old = get_test_map();
+        let new =
+            Map::<char>::from_str("1 2 3\n4 x 6\n7 8 9\na b c\nd e _\n").expect("Map should parse");
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.changes,
+            vec![
+                CellChange {
+                    pos: Pos::new(1, 1),
+                    old: Some('5'),
+                    new: Some('x'),
+                },
+                CellChange {
+                    pos: Pos::new(2, 4),
+                    old: Some('f'),
+                    new: None,
+                },
+            ]
+        );
+
+        let mut patched = old.clone();
+        assert_eq!(patched.apply_patch(&diff), Ok(()));
+        assert_eq!(patched, new);
+
+        assert_eq!(patched.apply_patch(&diff.reversed()), Ok(()));
+        assert_eq!(patched, old);
+    }
+
+    #[test]
+    fn diff_and_patch_between_sizes() {
+        let old = get_test_map();
+        let new = Map::<char>::from_str(
+            "1 2 3 4
+5 6 x 8
+",
+        )
+        .expect("Map should parse");
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.old_size, Pos::new(3, 5));
+        assert_eq!(diff.new_size, Pos::new(4, 2));
+
+        let mut patched = old.clone();
+        assert_eq!(patched.apply_patch(&diff), Ok(()));
+        assert_eq!(patched, new);
+
+        assert_eq!(patched.apply_patch(&diff.reversed()), Ok(()));
+        assert_eq!(patched, old);
+
+        assert_eq!(
+            patched.apply_patch(&new.diff(&old)),
+            Err(MapPatchError::SizeMismatch {
+                expected: Pos::new(4, 2),
+                found: Pos::new(3, 5),
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_patches_change_nothing() {
+        let mut map = get_test_map();
+
+        let change = |pos, old| CellChange {
+            pos,
+            old: Some(old),
+            new: Some('x'),
+        };
+
+        let patch = |changes| MapPatch {
+            old_size: Pos::new(3, 5),
+            new_size: Pos::new(3, 5),
+            changes,
+        };
+
+        assert_eq!(
+            map.apply_patch(&patch(vec![
+                change(Pos::new(0, 0), '1'),
+                change(Pos::new(5, 0), '1')
+            ])),
+            Err(MapPatchError::OutOfBounds {
+                pos: Pos::new(5, 0)
+            })
+        );
+        assert_eq!(
+            map.apply_patch(&patch(vec![
+                change(Pos::new(0, 0), '1'),
+                change(Pos::new(1, 0), '1')
+            ])),
+            Err(MapPatchError::Mismatch {
+                pos: Pos::new(1, 0)
+            })
+        );
+        assert_eq!(map, get_test_map());
+    }
+
+    #[test]
+    fn grid_of_any_item_type() {
+        let mut occupied = Map::<bool>::with_size(3, 2);
+        assert_eq!(occupied.size(), Pos::new(3, 2));
+
+        assert_eq!(occupied.set(&Pos::new(1, 0), true), Some(false));
+        assert_eq!(occupied.set(&Pos::new(1, 1), true), Some(false));
+        assert_eq!(occupied.set(&Pos::new(3, 0), true), None);
+
+        let free = occupied.regions(|occupied| !occupied);
+        assert_eq!(free.len(), 2);
+        assert_eq!(free.label(&Pos::new(0, 1)), Some(0));
+        assert_eq!(free.label(&Pos::new(2, 0)), Some(1));
+
+        let distances = Map::<u8>::new([[0, 1, 2], [1, 2, 3]]);
+        assert_eq!(
+            distances
+                .cells()
+                .filter(|(_, distance)| **distance == 2)
+                .map(|(pos, _)| pos)
+                .collect::<Vec<_>>(),
+            vec![Pos::new(2, 0), Pos::new(1, 1)]
+        );
+    }
+
+    #[test]
+    fn iterate_cells_with_pos() {
+        let mut map = Map::<char>::from_str("1 2\n_ 4").expect("Map should parse");
+
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            vec![
+                (Pos::new(0, 0), &'1'),
+                (Pos::new(1, 0), &'2'),
+                (Pos::new(1, 1), &'4'),
+            ]
+        );
+
+        for (pos, item) in &mut map {
+            if pos.y == 1 {
+                *item = 'x';
+            }
+        }
+        assert_eq!(map.to_level_string(), "1 2\n_ x\n");
+
+        assert_eq!(
+            map.into_iter().map(|(_, item)| item).collect::<String>(),
+            "12x"
+        );
+    }
+
+    #[test]
+    fn collect_and_convert_maps() {
+        let map = [(Pos::new(2, 0), 'a'), (Pos::new(0, 1), 'b')]
+            .into_iter()
+            .collect::<Map<char>>();
+        assert_eq!(map.to_level_string(), "_ _ a\nb _ _\n");
+        assert_eq!(
+            map.iter()
+                .map(|(pos, item)| (pos, *item))
+                .collect::<Map<_>>(),
+            map
+        );
+        assert_eq!(Map::<char>::from_iter([]).size(), Pos::ZERO);
+
+        let sums = Map::from_fn(3, 2, |pos| pos.x + pos.y);
+        assert_eq!(sums, Map::new([[0, 1, 2], [1, 2, 3]]));
+
+        assert_eq!(
+            get_test_map()
+                .map_values(char::is_numeric)
+                .cells()
+                .filter(|(_, numeric)| !**numeric)
+                .count(),
+            6
+        );
+        assert_eq!(
+            map.map_values(|item| item.to_ascii_uppercase())
+                .to_level_string(),
+            "_ _ A\nB _ _\n"
+        );
+    }
+
+    #[test]
+    fn crop_pad_and_resize() {
+        let map = get_test_map();
+
+        assert_eq!(
+            map.crop(URect::new(1, 1, 3, 3)).to_level_string(),
+            "5 6\n8 9\n"
+        );
+        assert_eq!(
+            map.crop(URect::new(2, 3, 10, 10)).to_level_string(),
+            "c\nf\n"
+        );
+        assert_eq!(
+            map.pad(1, None).to_level_string().lines().nth(1),
+            Some("_ 1 2 3 _")
+        );
+        assert_eq!(map.pad(1, Some('x')).size(), Pos::new(5, 7));
+        assert_eq!(map.pad(2, None).crop(URect::new(2, 2, 5, 7)), map);
+        assert_eq!(
+            map.resize(4, 2, Some('x')).to_level_string(),
+            "1 2 3 x\n4 5 6 x\n"
+        );
+    }
+
+    #[test]
+    fn view_rebases_positions() {
+        let map = get_test_map();
+        let view = map.view(URect::new(1, 2, 3, 10));
+
+        assert_eq!(view.size(), Pos::new(2, 3));
+        assert_eq!(view.get(&Pos::new(0, 0)), Some(&'8'));
+        assert_eq!(view.get(&Pos::new(2, 0)), None);
+        assert_eq!(view.to_map_pos(&Pos::new(1, 2)), Some(Pos::new(2, 4)));
+        assert_eq!(view.from_map_pos(&Pos::new(2, 4)), Some(Pos::new(1, 2)));
+        assert_eq!(view.from_map_pos(&Pos::new(0, 4)), None);
+        assert_eq!(view.neighbor(&Pos::new(1, 0), Horizontal::Right), None);
+        assert_eq!(
+            view.cells().map(|(_, item)| *item).collect::<String>(),
+            "89bcef"
+        );
+        assert_eq!(view.to_map(), map.crop(URect::new(1, 2, 3, 5)));
     }
 }
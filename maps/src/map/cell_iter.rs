@@ -0,0 +1,186 @@
+use std::iter::Enumerate;
+
+use crate::prelude::Pos;
+
+use super::{pos_of_index, Map};
+
+/// Iterates the non-void cells of a map together with their positions, in row-major order.
+#[derive(Clone)]
+pub struct Iter<'a, T> {
+    width: u32,
+    cells: Enumerate<std::slice::Iter<'a, Option<T>>>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    pub fn new(map: &'a Map<T>) -> Self {
+        Self {
+            width: map.width,
+            cells: map.items.iter().enumerate(),
+        }
+    }
+}
+
+/// Iterates the non-void cells of a map mutably together with their positions, in row-major order.
+pub struct IterMut<'a, T> {
+    width: u32,
+    cells: Enumerate<std::slice::IterMut<'a, Option<T>>>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    pub fn new(map: &'a mut Map<T>) -> Self {
+        Self {
+            width: map.width,
+            cells: map.items.iter_mut().enumerate(),
+        }
+    }
+}
+
+/// Moves the non-void cells out of a map together with their positions, in row-major order.
+pub struct IntoIter<T> {
+    width: u32,
+    cells: Enumerate<std::vec::IntoIter<Option<T>>>,
+}
+
+impl<T> IntoIter<T> {
+    pub fn new(map: Map<T>) -> Self {
+        Self {
+            width: map.width,
+            cells: map.items.into_iter().enumerate(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (Pos, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let width = self.width;
+        self.cells
+            .find_map(|(index, cell)| Some((pos_of_index(width, index), cell.as_ref()?)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.cells.size_hint().1)
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (Pos, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let width = self.width;
+        self.cells
+            .find_map(|(index, cell)| Some((pos_of_index(width, index), cell.as_mut()?)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.cells.size_hint().1)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = (Pos, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let width = self.width;
+        self.cells
+            .find_map(|(index, cell)| Some((pos_of_index(width, index), cell?)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.cells.size_hint().1)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Map<T> {
+    type Item = (Pos, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Map<T> {
+    type Item = (Pos, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> IntoIterator for Map<T> {
+    type Item = (Pos, T);
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<T> FromIterator<(Pos, T)> for Map<T> {
+    /// Places every item at its position. The map is just big enough for all positions,
+    /// and the cells without an item are void. A later item replaces an earlier one at the same position.
+    fn from_iter<I: IntoIterator<Item = (Pos, T)>>(iter: I) -> Self {
+        let cells = iter.into_iter().collect::<Vec<_>>();
+        let size = cells
+            .iter()
+            .fold(Pos::ZERO, |size, (pos, _)| size.max(*pos + 1));
+
+        let mut map = Self {
+            width: size.x,
+            height: size.y,
+            items: std::iter::repeat_with(|| None)
+                .take(size.x as usize * size.y as usize)
+                .collect(),
+        };
+
+        for (pos, item) in cells {
+            if let Some(index) = map.index_of(pos) {
+                map.items[index] = Some(item);
+            }
+        }
+
+        map
+    }
+}
+
+impl<T> Map<T> {
+    /// Creates a map of the given size with the item `f` returns for each position.
+    #[must_use]
+    pub fn from_fn(width: u32, height: u32, mut f: impl FnMut(Pos) -> T) -> Self {
+        Self {
+            width,
+            height,
+            items: (0..width as usize * height as usize)
+                .map(|index| Some(f(pos_of_index(width, index))))
+                .collect(),
+        }
+    }
+
+    /// All non-void cells together with their positions, in row-major order.
+    #[must_use]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self)
+    }
+
+    /// All non-void cells mutably together with their positions, in row-major order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut::new(self)
+    }
+
+    /// Converts every item with `f`, keeping the void cells.
+    #[must_use]
+    pub fn map_values<U>(self, mut f: impl FnMut(T) -> U) -> Map<U> {
+        Map {
+            width: self.width,
+            height: self.height,
+            items: self
+                .items
+                .into_iter()
+                .map(|cell| cell.map(&mut f))
+                .collect(),
+        }
+    }
+}
@@ -1,57 +1,51 @@
-use std::fmt::Debug;
-use std::str::FromStr;
-
-use crate::{
-    prelude::{Offset, Pos},
-    W,
-};
+use std::iter::{Flatten, StepBy};
 
 use super::Map;
 
-pub struct ColumnIter<'a, T: FromStr + Debug>(&'a Map<T>, Pos)
-where
-    <T as FromStr>::Err: Debug;
+/// Iterates the non-void cells of a column.
+#[derive(Clone)]
+pub struct ColumnIter<'a, T>(Flatten<StepBy<std::slice::Iter<'a, Option<T>>>>);
+
+impl<'a, T> ColumnIter<'a, T> {
+    pub fn new(map: &'a Map<T>, column: u32) -> Self {
+        let items = if column < map.width {
+            &map.items[column as usize..]
+        } else {
+            &[]
+        };
 
-impl<'a, T: FromStr + Debug> ColumnIter<'a, T>
-where
-    <T as FromStr>::Err: Debug,
-{
-    pub const fn new(map: &'a Map<T>, column: u32) -> Self {
-        Self(map, Pos::new(column, 0))
+        Self(items.iter().step_by(map.width.max(1) as usize).flatten())
     }
 }
-pub struct ColumnsIter<'a, T: FromStr + Debug>(&'a Map<T>, u32)
-where
-    <T as FromStr>::Err: Debug;
 
-impl<'a, T: FromStr + Debug> ColumnsIter<'a, T>
-where
-    <T as FromStr>::Err: Debug,
-{
+#[derive(Clone)]
+pub struct ColumnsIter<'a, T>(&'a Map<T>, u32);
+
+impl<'a, T> ColumnsIter<'a, T> {
     pub const fn new(map: &'a Map<T>) -> Self {
         Self(map, 0)
     }
 }
 
-impl<'a, T: FromStr + Debug> Iterator for ColumnIter<'a, T>
-where
-    <T as FromStr>::Err: Debug,
-{
+impl<'a, T> Iterator for ColumnIter<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let current = self.0.get(&self.1)?;
+        self.0.next()
+    }
 
-        self.1 = W(&self.1).try_add(&Offset::Y)?;
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
 
-        Some(current)
+impl<T> DoubleEndedIterator for ColumnIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
     }
 }
 
-impl<'a, T: FromStr + Debug> Iterator for ColumnsIter<'a, T>
-where
-    <T as FromStr>::Err: Debug,
-{
+impl<'a, T> Iterator for ColumnsIter<'a, T> {
     type Item = ColumnIter<'a, T>;
 
     fn next(&mut self) -> Option<Self::Item> {
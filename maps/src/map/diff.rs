@@ -0,0 +1,118 @@
+use crate::prelude::{Grid, MapPatchError, Pos};
+
+use super::Map;
+
+/// A cell which differs between two maps, as found by [`Map::diff`]. `None` is a void cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellChange<T> {
+    pub pos: Pos,
+    pub old: Option<T>,
+    pub new: Option<T>,
+}
+
+impl<T> CellChange<T> {
+    /// The change which undoes this one.
+    #[must_use]
+    pub fn reversed(self) -> Self {
+        Self {
+            pos: self.pos,
+            old: self.new,
+            new: self.old,
+        }
+    }
+}
+
+/// The changes which turn one map into another, as found by [`Map::diff`].
+/// The maps can have different sizes, the cells outside of one of them count as void cells there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapPatch<T> {
+    pub old_size: Pos,
+    pub new_size: Pos,
+    pub changes: Vec<CellChange<T>>,
+}
+
+impl<T> MapPatch<T> {
+    /// The patch which undoes this one.
+    #[must_use]
+    pub fn reversed(self) -> Self {
+        Self {
+            old_size: self.new_size,
+            new_size: self.old_size,
+            changes: self.changes.into_iter().map(CellChange::reversed).collect(),
+        }
+    }
+
+    /// Whether the patch changes nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.old_size == self.new_size && self.changes.is_empty()
+    }
+}
+
+impl<T: Clone + PartialEq> Map<T> {
+    /// The cells which have to change to turn this map into `other`, in row-major order, together with both sizes.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> MapPatch<T> {
+        let size = self.size().max(other.size());
+
+        let changes = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| Pos::new(x, y)))
+            .filter_map(|pos| {
+                let old = self.get(&pos);
+                let new = other.get(&pos);
+
+                (old != new).then(|| CellChange {
+                    pos,
+                    old: old.cloned(),
+                    new: new.cloned(),
+                })
+            })
+            .collect();
+
+        MapPatch {
+            old_size: self.size(),
+            new_size: other.size(),
+            changes,
+        }
+    }
+
+    /// Applies a [`Map::diff`], either all of it or nothing.
+    /// The map is resized to the new size of the patch first, keeping the top left corner in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the map doesn't have the old size of the patch, a change is outside of both sizes,
+    /// or the cell doesn't hold the old value of the change.
+    pub fn apply_patch(&mut self, patch: &MapPatch<T>) -> Result<(), MapPatchError> {
+        if self.size() != patch.old_size {
+            return Err(MapPatchError::SizeMismatch {
+                expected: patch.old_size,
+                found: self.size(),
+            });
+        }
+
+        let max_size = patch.old_size.max(patch.new_size);
+
+        for change in &patch.changes {
+            if change.pos.cmpge(max_size).any() {
+                return Err(MapPatchError::OutOfBounds { pos: change.pos });
+            }
+
+            if self.get(&change.pos) != change.old.as_ref() {
+                return Err(MapPatchError::Mismatch { pos: change.pos });
+            }
+        }
+
+        if patch.new_size != patch.old_size {
+            *self = self.resize(patch.new_size.x, patch.new_size.y, None);
+        }
+
+        for change in &patch.changes {
+            if let Some(index) = self.index_of(change.pos) {
+                self.items[index].clone_from(&change.new);
+            }
+        }
+
+        Ok(())
+    }
+}
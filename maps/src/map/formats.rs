@@ -0,0 +1,329 @@
+//! Structured serde formats for a [`Map`] besides the level text format, for `#[serde(with = "...")]`.
+//! They work with any self-describing format, like RON, JSON and TOML.
+
+/// The map as an array of rows of cells, where a void cell is `None` (`null` in JSON).
+///
+/// For example `[[Some(Empty), Some(RoundRock)], [Some(Target), None]]` in RON, where the `implicit_some`
+/// extension allows leaving out the `Some`, like the level files do. All rows need the same length.
+/// TOML has no `None`, so it only works for maps without void cells.
+pub mod rows {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::prelude::Map;
+
+    /// # Errors
+    ///
+    /// Returns an error if the serializer fails.
+    pub fn serialize<T: Serialize, S: Serializer>(
+        map: &Map<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map.rows())
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the data is not an array of rows of cells, or the rows have different lengths.
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Map<T>, D::Error> {
+        let rows = Vec::<Vec<Option<T>>>::deserialize(deserializer)?;
+
+        Map::try_from_cells(rows).map_err(D::Error::custom)
+    }
+}
+
+/// The map as its size and a list of `(pos, item)` entries over a `fill` item.
+///
+/// For example `(width: 3, height: 2, fill: Empty, cells: [((1, 0), RoundRock)], void: [(2, 1)])`.
+/// `fill` defaults to `T::default()`, and `void` lists the void cells.
+pub mod sparse {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::prelude::{Map, Pos};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Sparse<T> {
+        width: u32,
+        height: u32,
+        #[serde(default)]
+        fill: T,
+        #[serde(default)]
+        cells: Vec<(Pos, T)>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        void: Vec<Pos>,
+    }
+
+    /// Writes every cell which isn't `T::default()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the serializer fails.
+    pub fn serialize<T, S>(map: &Map<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + Default + PartialEq,
+        S: Serializer,
+    {
+        let fill = T::default();
+
+        Sparse {
+            width: map.width,
+            height: map.height,
+            fill: &fill,
+            cells: map.iter().filter(|(_, item)| **item != fill).collect(),
+            void: (0..map.height)
+                .flat_map(|y| (0..map.width).map(move |x| Pos::new(x, y)))
+                .filter(|pos| map.is_void(pos))
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the data doesn't match the format, or a cell is outside of the map.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Map<T>, D::Error>
+    where
+        T: Deserialize<'de> + Default + Clone,
+        D: Deserializer<'de>,
+    {
+        let sparse = Sparse::<T>::deserialize(deserializer)?;
+
+        let mut map = Map {
+            width: sparse.width,
+            height: sparse.height,
+            items: vec![Some(sparse.fill); sparse.width as usize * sparse.height as usize],
+        };
+
+        let cells = sparse
+            .cells
+            .into_iter()
+            .map(|(pos, item)| (pos, Some(item)));
+        let void = sparse.void.into_iter().map(|pos| (pos, None));
+
+        for (pos, cell) in cells.chain(void) {
+            let index = map
+                .index_of(pos)
+                .ok_or_else(|| D::Error::custom(format!("the cell {pos} is outside of the map")))?;
+
+            map.items[index] = cell;
+        }
+
+        Ok(map)
+    }
+}
+
+/// Reads the map from the level text format, from [`rows`] or from [`sparse`], whichever the data looks like.
+pub mod any_format {
+    use std::{
+        fmt::{Display, Formatter},
+        marker::PhantomData,
+        str::FromStr,
+    };
+
+    use serde::{
+        de::{
+            value::{MapAccessDeserializer, SeqAccessDeserializer},
+            Error, MapAccess, SeqAccess, Visitor,
+        },
+        Deserialize, Deserializer,
+    };
+
+    use crate::prelude::Map;
+
+    use super::{rows, sparse};
+
+    /// # Errors
+    ///
+    /// Returns an error if the data doesn't match any of the formats.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Map<T>, D::Error>
+    where
+        T: FromStr + Deserialize<'de> + Default + Clone,
+        <T as FromStr>::Err: Display,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(AnyFormatVisitor(PhantomData))
+    }
+
+    struct AnyFormatVisitor<T>(PhantomData<T>);
+
+    impl<'de, T> Visitor<'de> for AnyFormatVisitor<T>
+    where
+        T: FromStr + Deserialize<'de> + Default + Clone,
+        <T as FromStr>::Err: Display,
+    {
+        type Value = Map<T>;
+
+        fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+            f.write_str("a level string, an array of rows or a sparse map")
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+            Map::from_str(v).map_err(E::custom)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            rows::deserialize(SeqAccessDeserializer::new(seq))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            sparse::deserialize(MapAccessDeserializer::new(map))
+        }
+    }
+}
+
+#[cfg(test)]
+mod formats_tests {
+    use std::str::FromStr;
+
+    use ron::extensions::Extensions;
+    use serde::{Deserialize, Serialize};
+
+    use crate::prelude::{Map, Tile};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Rows {
+        #[serde(with = "super::rows")]
+        map: Map,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sparse {
+        #[serde(with = "super::sparse")]
+        map: Map,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct AnyFormat {
+        #[serde(deserialize_with = "super::any_format::deserialize")]
+        map: Map,
+    }
+
+    fn get_test_map() -> Map {
+        Map::<Tile>::from_str(
+            "\
+. o ◢
++o _ .
+# _ _",
+        )
+        .expect("Map should parse")
+    }
+
+    #[test]
+    fn rows_in_every_format() {
+        let rows = Rows {
+            map: get_test_map(),
+        };
+
+        let ron = ron::to_string(&rows).expect("Map should serialize");
+        assert_eq!(
+            ron,
+            "(map:[[Some(Empty),Some(RoundRock),Some(SingleReflect(TopLeft))],[Some(Tile(floor:Target,object:RoundRock)),None,Some(Empty)],[Some(SquareRock),None,None]])"
+        );
+        assert_eq!(ron::from_str::<Rows>(&ron).ok().as_ref(), Some(&rows));
+
+        let implicit_some =
+            ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
+        let ron = implicit_some
+            .to_string(&rows)
+            .expect("Map should serialize");
+        assert_eq!(
+            ron,
+            "(map:[[Empty,RoundRock,SingleReflect(TopLeft)],[Tile(floor:Target,object:RoundRock),None,Empty],[SquareRock,None,None]])"
+        );
+        assert_eq!(
+            implicit_some.from_str::<Rows>(&ron).ok().as_ref(),
+            Some(&rows)
+        );
+
+        let json = serde_json::to_string(&rows).expect("Map should serialize");
+        assert_eq!(
+            serde_json::from_str::<Rows>(&json).ok().as_ref(),
+            Some(&rows)
+        );
+
+        assert!(
+            toml::to_string(&rows).is_err(),
+            "TOML can't write void cells"
+        );
+        let rows = Rows {
+            map: Map::from_str(". o\n+ #").expect("Map should parse"),
+        };
+        let toml = toml::to_string(&rows).expect("Map should serialize");
+        assert_eq!(toml::from_str::<Rows>(&toml).ok().as_ref(), Some(&rows));
+    }
+
+    #[test]
+    fn rows_need_the_same_length() {
+        assert!(
+            ron::from_str::<Rows>("(map: [[Some(Empty), Some(Empty)], [Some(Target)]])").is_err()
+        );
+        assert!(
+            ron::from_str::<Rows>("(map: [[Some(Empty), None], [Some(Target), None]])").is_ok()
+        );
+    }
+
+    #[test]
+    fn sparse_in_every_format() {
+        let sparse = Sparse {
+            map: get_test_map(),
+        };
+
+        let ron = ron::to_string(&sparse).expect("Map should serialize");
+        assert_eq!(
+            ron,
+            "(map:(width:3,height:3,fill:Empty,cells:[((1,0),RoundRock),((2,0),SingleReflect(TopLeft)),((0,1),Tile(floor:Target,object:RoundRock)),((0,2),SquareRock)],void:[(1,1),(1,2),(2,2)]))"
+        );
+        assert_eq!(ron::from_str::<Sparse>(&ron).ok().as_ref(), Some(&sparse));
+
+        let json = serde_json::to_string(&sparse).expect("Map should serialize");
+        assert_eq!(
+            serde_json::from_str::<Sparse>(&json).ok().as_ref(),
+            Some(&sparse)
+        );
+
+        let toml = toml::to_string(&sparse).expect("Map should serialize");
+        assert_eq!(toml::from_str::<Sparse>(&toml).ok().as_ref(), Some(&sparse));
+
+        assert!(
+            ron::from_str::<Sparse>("(map: (width: 1, height: 1, cells: [((1, 0), RoundRock)]))")
+                .is_err(),
+            "cells outside of the map are rejected"
+        );
+    }
+
+    #[test]
+    fn any_format_by_shape() {
+        let expected = Map::<Tile>::from_str(". o\n+ .").expect("Map should parse");
+
+        for ron in [
+            r#"(map: ". o\n+ .")"#,
+            "(map: [[Some(Empty), Some(RoundRock)], [Some(Target), Some(Empty)]])",
+            "(map: (width: 2, height: 2, cells: [((1, 0), RoundRock), ((0, 1), Target)]))",
+        ] {
+            assert_eq!(
+                ron::from_str::<AnyFormat>(ron).map(|any| any.map),
+                Ok(expected.clone()),
+                "{ron}"
+            );
+        }
+
+        assert_eq!(
+            serde_json::from_str::<AnyFormat>(
+                r#"{"map": [["Empty", "RoundRock"], ["Target", "Empty"]]}"#
+            )
+            .ok()
+            .map(|any| any.map),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            toml::from_str::<AnyFormat>(
+                "[map]\nwidth = 2\nheight = 2\ncells = [[[1, 0], \"RoundRock\"], [[0, 1], \"Target\"]]"
+            )
+            .ok()
+            .map(|any| any.map),
+            Some(expected)
+        );
+    }
+}
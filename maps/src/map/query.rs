@@ -0,0 +1,107 @@
+use bevy_math::URect;
+
+use crate::{
+    prelude::{Horizontal, Offset, Pos, Wrap},
+    W,
+};
+
+use super::Map;
+
+/// The offsets of the eight surrounding cells, clockwise starting at the top.
+const NEIGHBORS8: [Offset; 8] = [
+    Offset::new(0, -1),
+    Offset::new(1, -1),
+    Offset::new(1, 0),
+    Offset::new(1, 1),
+    Offset::new(0, 1),
+    Offset::new(-1, 1),
+    Offset::new(-1, 0),
+    Offset::new(-1, -1),
+];
+
+impl<T> Map<T> {
+    /// The neighbouring position in `direction`, if it is part of the map.
+    #[must_use]
+    pub fn neighbor(&self, pos: &Pos, direction: Horizontal) -> Option<Pos> {
+        self.neighbor_at(*pos, direction.to_offset())
+    }
+
+    fn neighbor_at(&self, pos: Pos, offset: Offset) -> Option<Pos> {
+        W(&pos).try_add_in_wrapped_map(self, &offset, Wrap::Off)
+    }
+
+    /// Walks from `start` in `direction`, yielding every cell after `start` until the edge of the map or a void cell.
+    #[must_use]
+    pub fn ray(&self, start: &Pos, direction: Horizontal) -> RayIter<'_, T> {
+        RayIter {
+            map: self,
+            next: self.neighbor(start, direction),
+            direction,
+        }
+    }
+
+    /// The first cell of the [`Map::ray`] from `start` in `direction` which matches `predicate`.
+    pub fn first_blocking(
+        &self,
+        start: &Pos,
+        direction: Horizontal,
+        mut predicate: impl FnMut(&T) -> bool,
+    ) -> Option<(Pos, &T)> {
+        self.ray(start, direction).find(|(_, item)| predicate(item))
+    }
+
+    /// The non-void cells sharing an edge with `pos`, clockwise starting at the top.
+    pub fn neighbors4(&self, pos: &Pos) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        let pos = *pos;
+
+        NEIGHBORS8
+            .iter()
+            .step_by(2)
+            .filter_map(move |offset| self.cell_at(pos, *offset))
+    }
+
+    /// The non-void cells sharing an edge or a corner with `pos`, clockwise starting at the top.
+    pub fn neighbors8(&self, pos: &Pos) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        let pos = *pos;
+
+        NEIGHBORS8
+            .iter()
+            .filter_map(move |offset| self.cell_at(pos, *offset))
+    }
+
+    fn cell_at(&self, pos: Pos, offset: Offset) -> Option<(Pos, &T)> {
+        let neighbor = self.neighbor_at(pos, offset)?;
+
+        Some((neighbor, &self[neighbor]))
+    }
+
+    /// The non-void cells inside `rect` in row-major order.
+    /// `rect.max` is exclusive, and the parts of `rect` outside of the map are ignored.
+    pub fn cells_in_rect(&self, rect: URect) -> impl Iterator<Item = (Pos, &T)> {
+        let max = rect.max.min(Pos::new(self.width, self.height));
+
+        (rect.min.y..max.y)
+            .flat_map(move |y| (rect.min.x..max.x).map(move |x| Pos::new(x, y)))
+            .filter_map(|pos| Some((pos, self.get(&pos)?)))
+    }
+}
+
+/// Iterates the cells of a [`Map::ray`].
+pub struct RayIter<'a, T> {
+    map: &'a Map<T>,
+    next: Option<Pos>,
+    direction: Horizontal,
+}
+
+impl<'a, T> Iterator for RayIter<'a, T> {
+    type Item = (Pos, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        let item = self.map.get(&current)?;
+
+        self.next = self.map.neighbor(&current, self.direction);
+
+        Some((current, item))
+    }
+}
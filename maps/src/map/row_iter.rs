@@ -1,69 +1,76 @@
-use std::fmt::Debug;
-use std::str::FromStr;
-
-use crate::{
-    prelude::{Offset, Pos},
-    W,
-};
+use std::{iter::Flatten, ops::Range};
 
 use super::Map;
 
+/// Iterates the non-void cells of a row.
 #[derive(Clone)]
-pub struct RowIter<'a, T: FromStr + Debug>(&'a Map<T>, Pos)
-where
-    <T as FromStr>::Err: Debug;
+pub struct RowIter<'a, T>(Flatten<std::slice::Iter<'a, Option<T>>>);
 
-impl<'a, T: FromStr + Debug> RowIter<'a, T>
-where
-    <T as FromStr>::Err: Debug,
-{
-    pub const fn new(map: &'a Map<T>, row: u32) -> Self {
-        Self(map, Pos::new(0, row))
+impl<'a, T> RowIter<'a, T> {
+    pub fn new(map: &'a Map<T>, row: u32) -> Self {
+        Self(map.row(row).unwrap_or_default().iter().flatten())
     }
 }
 
-#[derive(Clone)]
-pub struct RowsIter<'a, T: FromStr + Debug>(&'a Map<T>, u32)
-where
-    <T as FromStr>::Err: Debug;
+/// Iterates the rows as slices of cells, including void cells.
+/// A map without columns still has `height` rows, which are all empty.
+pub struct RowsIter<'a, T> {
+    map: &'a Map<T>,
+    rows: Range<u32>,
+}
+
+impl<T> Clone for RowsIter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map,
+            rows: self.rows.clone(),
+        }
+    }
+}
 
-impl<'a, T: FromStr + Debug> RowsIter<'a, T>
-where
-    <T as FromStr>::Err: Debug,
-{
+impl<'a, T> RowsIter<'a, T> {
     pub const fn new(map: &'a Map<T>) -> Self {
-        Self(map, 0)
+        Self {
+            map,
+            rows: 0..map.height,
+        }
     }
 }
 
-impl<'a, T: FromStr + Debug> Iterator for RowIter<'a, T>
-where
-    <T as FromStr>::Err: Debug,
-{
+impl<'a, T> Iterator for RowIter<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let current = self.0.get(&self.1)?;
+        self.0.next()
+    }
 
-        self.1 = W(&self.1).try_add(&Offset::X)?;
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
 
-        Some(current)
+impl<T> DoubleEndedIterator for RowIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
     }
 }
 
-impl<'a, T: FromStr + Debug> Iterator for RowsIter<'a, T>
-where
-    <T as FromStr>::Err: Debug,
-{
-    type Item = RowIter<'a, T>;
+impl<'a, T> Iterator for RowsIter<'a, T> {
+    type Item = &'a [Option<T>];
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.1 >= self.0.height() {
-            return None;
-        }
+        self.map.row(self.rows.next()?)
+    }
 
-        self.1 += 1;
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
 
-        Some(RowIter::new(self.0, self.1 - 1))
+impl<T> DoubleEndedIterator for RowsIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.map.row(self.rows.next_back()?)
     }
 }
+
+impl<T> ExactSizeIterator for RowsIter<'_, T> {}
@@ -0,0 +1,186 @@
+use bevy_math::URect;
+
+use crate::{
+    prelude::{Grid, Horizontal, Offset, Pos, Tile},
+    W,
+};
+
+use super::{pos_of_index, Map};
+
+impl<T: Clone> Map<T> {
+    /// The part of the map inside `rect`. `rect.max` is exclusive, and the parts of `rect` outside of the map are left out.
+    #[must_use]
+    pub fn crop(&self, rect: URect) -> Self {
+        self.view(rect).to_map()
+    }
+
+    /// Surrounds the map with `margin` cells of `fill` on every side, where `None` pads with void cells.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the padded map is too big.
+    #[must_use]
+    pub fn pad(&self, margin: u16, fill: Option<T>) -> Self {
+        let padded = |size: u32| {
+            size.checked_add(2 * u32::from(margin))
+                .expect("Map is too big")
+        };
+
+        self.rebuilt(
+            padded(self.width),
+            padded(self.height),
+            Offset::splat(i32::from(margin)),
+            fill,
+        )
+    }
+
+    /// Changes the size of the map, keeping the top left corner in place.
+    /// New cells are `fill`, where `None` adds void cells.
+    #[must_use]
+    pub fn resize(&self, width: u32, height: u32, fill: Option<T>) -> Self {
+        self.rebuilt(width, height, Offset::ZERO, fill)
+    }
+
+    /// A map of the given size, with every cell of this map moved by `offset`.
+    fn rebuilt(&self, width: u32, height: u32, offset: Offset, fill: Option<T>) -> Self {
+        let mut items = vec![fill; width as usize * height as usize];
+
+        for (index, item) in items.iter_mut().enumerate() {
+            let old_cell = W(&pos_of_index(width, index))
+                .try_add(&-offset)
+                .and_then(|old_pos| self.get_cell(&old_pos));
+
+            if let Some(old_cell) = old_cell {
+                item.clone_from(old_cell);
+            }
+        }
+
+        Self {
+            width,
+            height,
+            items,
+        }
+    }
+}
+
+impl<T> Map<T> {
+    /// A view of the part of the map inside `rect`, whose positions start at the top left corner of `rect`.
+    /// `rect.max` is exclusive, and the parts of `rect` outside of the map are left out.
+    #[must_use]
+    pub fn view(&self, rect: URect) -> MapView<'_, T> {
+        MapView::new(self, rect)
+    }
+}
+
+/// A borrowed rectangle of a [`Map`], like a viewport.
+/// Its positions are relative to the top left corner of the rectangle.
+pub struct MapView<'a, T = Tile> {
+    map: &'a Map<T>,
+    rect: URect,
+}
+
+impl<T> Clone for MapView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for MapView<'_, T> {}
+
+impl<'a, T> MapView<'a, T> {
+    /// Creates a view of the part of `map` inside `rect`, see [`Map::view`].
+    #[must_use]
+    pub fn new(map: &'a Map<T>, rect: URect) -> Self {
+        let max = rect.max.min(Pos::new(map.width, map.height));
+        let min = rect.min.min(max);
+
+        Self {
+            map,
+            rect: URect::from_corners(min, max),
+        }
+    }
+
+    /// The part of the map which is visible, in the positions of the map.
+    #[must_use]
+    pub const fn rect(&self) -> URect {
+        self.rect
+    }
+
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.rect.width()
+    }
+
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.rect.height()
+    }
+
+    /// The position in the map of the position `pos` in the view, if it is inside of the view.
+    #[must_use]
+    pub fn to_map_pos(&self, pos: &Pos) -> Option<Pos> {
+        (pos.x < self.width() && pos.y < self.height()).then(|| *pos + self.rect.min)
+    }
+
+    /// The position in the view of the position `map_pos` in the map, if it is inside of the view.
+    #[must_use]
+    pub fn from_map_pos(&self, map_pos: &Pos) -> Option<Pos> {
+        let pos = Pos::new(
+            map_pos.x.checked_sub(self.rect.min.x)?,
+            map_pos.y.checked_sub(self.rect.min.y)?,
+        );
+
+        (pos.x < self.width() && pos.y < self.height()).then_some(pos)
+    }
+
+    /// The item at `pos` in the view, or `None` if it is void or outside of the view.
+    #[must_use]
+    pub fn get(&self, pos: &Pos) -> Option<&'a T> {
+        self.map.get(&self.to_map_pos(pos)?)
+    }
+
+    /// The rows of the view as slices of cells, including void cells.
+    pub fn rows(&self) -> impl Iterator<Item = &'a [Option<T>]> + '_ {
+        let columns = self.rect.min.x as usize..self.rect.max.x as usize;
+
+        (self.rect.min.y..self.rect.max.y)
+            .filter_map(move |y| self.map.row(y)?.get(columns.clone()))
+    }
+
+    /// Copies the visible part into its own map.
+    #[must_use]
+    pub fn to_map(&self) -> Map<T>
+    where
+        T: Clone,
+    {
+        Map::from_cells(self.rows().map(<[Option<T>]>::to_vec))
+    }
+}
+
+impl<T> Grid for MapView<'_, T> {
+    type Pos = Pos;
+    type Direction = Horizontal;
+    type Item = T;
+
+    const DIRECTIONS: &'static [Horizontal] = &Horizontal::ALL;
+
+    fn size(&self) -> Pos {
+        self.rect.size()
+    }
+
+    fn get(&self, pos: &Pos) -> Option<&T> {
+        Self::get(self, pos)
+    }
+
+    fn neighbor(&self, pos: &Pos, direction: Horizontal) -> Option<Pos> {
+        W(pos)
+            .try_add(&direction.to_offset())
+            .filter(|neighbor| self.get(neighbor).is_some())
+    }
+
+    fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| Pos::new(x, y)))
+            .filter(|pos| self.get(pos).is_some())
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::prelude::{Map, MapParseError, Pos, RockKind};
+
+/// The pairs of portals on a map, so the partner of a portal can be looked up without searching the map.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Portals(HashMap<Pos, Pos>);
+
+impl Portals {
+    /// The position of the other portal with the same id as the portal at `pos`.
+    #[must_use]
+    pub fn partner(&self, pos: &Pos) -> Option<Pos> {
+        self.0.get(pos).copied()
+    }
+
+    fn from_groups(groups: BTreeMap<char, Vec<Pos>>) -> Self {
+        Self(
+            groups
+                .into_values()
+                .filter_map(|group| match group[..] {
+                    [first, second] => Some([(first, second), (second, first)]),
+                    _ => None,
+                })
+                .flatten()
+                .collect(),
+        )
+    }
+}
+
+impl Map {
+    /// Pairs up the portals with the same id. Portals without exactly one partner are left out.
+    #[must_use]
+    pub fn portals(&self) -> Portals {
+        Portals::from_groups(self.portal_groups())
+    }
+
+    /// Checks that every portal has exactly one partner, and pairs them up.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error for the first portal without exactly one partner.
+    pub fn validate_portals(&self) -> Result<Portals, MapParseError> {
+        let groups = self.portal_groups();
+
+        match groups.iter().find(|(_, group)| group.len() != 2) {
+            Some((portal, group)) => Err(MapParseError::UnpairedPortal {
+                portal: *portal,
+                count: group.len(),
+            }),
+            None => Ok(Portals::from_groups(groups)),
+        }
+    }
+
+    /// The positions of the portals with each id.
+    fn portal_groups(&self) -> BTreeMap<char, Vec<Pos>> {
+        let mut groups = BTreeMap::<char, Vec<Pos>>::new();
+
+        for (pos, tile) in self {
+            if let RockKind::Portal(id) = tile.object {
+                groups.entry(id).or_default().push(pos);
+            }
+        }
+
+        groups
+    }
+}
+
+#[cfg(test)]
+mod portal_tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn find_partner() {
+        let map = Map::from_str("A . 1\n1 . A").expect("Map should parse");
+        let portals = map.validate_portals().expect("Portals should be paired");
+
+        assert_eq!(portals.partner(&Pos::new(0, 0)), Some(Pos::new(2, 1)));
+        assert_eq!(portals.partner(&Pos::new(0, 1)), Some(Pos::new(2, 0)));
+        assert_eq!(portals.partner(&Pos::new(1, 0)), None);
+        assert_eq!(portals, map.portals());
+
+        let map = Map::from_str("A . B\nB . B").expect("Map should parse");
+        assert_eq!(map.portals().partner(&Pos::new(0, 0)), None);
+        assert_eq!(map.portals().partner(&Pos::new(2, 0)), None);
+    }
+
+    #[test]
+    fn unpaired_portals() {
+        assert_eq!(
+            Map::from_str("A . B\nB . B")
+                .expect("Map should parse")
+                .validate_portals(),
+            Err(MapParseError::UnpairedPortal {
+                portal: 'A',
+                count: 1
+            })
+        );
+        assert_eq!(
+            Map::from_str("A . B _\nB . B A")
+                .expect("Map should parse")
+                .validate_portals(),
+            Err(MapParseError::UnpairedPortal {
+                portal: 'B',
+                count: 3
+            })
+        );
+    }
+}
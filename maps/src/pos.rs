@@ -1,6 +1,6 @@
 use crate::W;
 
-use super::prelude::{Map, Offset, Pos};
+use super::prelude::{Map, Offset, Pos, Wrap};
 
 impl W<&Pos> {
     #[must_use]
@@ -11,11 +11,38 @@ impl W<&Pos> {
         })
     }
 
+    /// Like [`W::try_add`], but the result also has to lie within a map of the given size.
+    #[must_use]
+    pub fn try_add_within(&self, rhs: &Offset, width: u32, height: u32) -> Option<Pos> {
+        self.try_add(rhs)
+            .filter(|pos| pos.x < width && pos.y < height)
+    }
+
     #[must_use]
     pub fn try_add_in_map(&self, map: &Map, rhs: &Offset) -> Option<Pos> {
-        let pos = self.try_add(rhs)?;
+        self.try_add_in_wrapped_map(map, rhs, Wrap::Off)
+    }
+
+    /// Like [`W::try_add_in_map`], but leaving the map over a wrapping edge comes back on the opposite edge.
+    #[must_use]
+    pub fn try_add_in_wrapped_map<T>(&self, map: &Map<T>, rhs: &Offset, wrap: Wrap) -> Option<Pos> {
+        let wrap_axis = |value: u32, offset: i32, size: u32, wraps: bool| {
+            let value = i64::from(value) + i64::from(offset);
+            let value = if wraps && size > 0 {
+                value.rem_euclid(i64::from(size))
+            } else {
+                value
+            };
+
+            u32::try_from(value).ok()
+        };
 
-        if pos.x < map.width() && pos.y < map.height() {
+        let pos = Pos {
+            x: wrap_axis(self.0.x, rhs.x, map.width(), wrap.wraps_x())?,
+            y: wrap_axis(self.0.y, rhs.y, map.height(), wrap.wraps_y())?,
+        };
+
+        if map.has_cell(&pos) {
             Some(pos)
         } else {
             None
@@ -24,7 +51,7 @@ impl W<&Pos> {
 }
 
 impl W<&mut Pos> {
-    pub fn apply(&mut self, rhs: &Pos) {
+    pub const fn apply(&mut self, rhs: &Pos) {
         self.0.x = rhs.x;
         self.0.y = rhs.y;
     }
@@ -44,6 +71,26 @@ mod position_tests {
         assert_eq!(result, Pos { x: 4, y: 6 });
     }
 
+    #[test]
+    fn add_in_wrapped_map() {
+        let map = Map::<crate::prelude::Tile>::with_size(3, 2);
+        let pos = Pos::new(0, 1);
+
+        assert_eq!(W(&pos).try_add_in_map(&map, &Offset::NEG_X), None);
+        assert_eq!(
+            W(&pos).try_add_in_wrapped_map(&map, &Offset::NEG_X, Wrap::Horizontal),
+            Some(Pos::new(2, 1))
+        );
+        assert_eq!(
+            W(&pos).try_add_in_wrapped_map(&map, &Offset::NEG_X, Wrap::Vertical),
+            None
+        );
+        assert_eq!(
+            W(&pos).try_add_in_wrapped_map(&map, &Offset::Y, Wrap::Both),
+            Some(Pos::new(0, 0))
+        );
+    }
+
     #[test]
     fn test_pos_add_assign() {
         let mut pos1 = Pos { x: 1, y: 2 };
@@ -1,9 +1,27 @@
 pub use super::{
-    direction::{Diagonal, Horizontal},
-    map::Map,
-    rock::{Rock, RockKind},
+    direction::{Diagonal, Direction, HexDirection, Horizontal},
+    error::{MapParseError, MapPatchError},
+    floor::Floor,
+    glyph::{LevelGlyph, VOID_GLYPH},
+    grid::{Grid, GridMut},
+    hex::{axial_to_offset, offset_to_axial, HexAxis, HexLineIter, HexMap},
+    map::{
+        diff::{CellChange, MapPatch},
+        formats as map_formats,
+        query::RayIter,
+        view::MapView,
+        Map,
+    },
+    portal::Portals,
+    region::Regions,
+    rock::{Mirror, Rock, RockColor, RockKind, Splitter},
     tile::Tile,
+    transform::{Transform, Transformable},
+    wrap::{Wrap, WrappedMap},
 };
 
 pub use bevy_math::IVec2 as Offset;
+/// Axial hex coordinates, `x` is the q and `y` is the r axis.
+pub use bevy_math::IVec2 as Axial;
+pub use bevy_math::URect;
 pub use bevy_math::UVec2 as Pos;
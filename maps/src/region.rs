@@ -0,0 +1,51 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::prelude::Pos;
+
+/// The connected components of a grid, as found by [`Grid::regions`](crate::prelude::Grid::regions).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Regions<P: Eq + Hash = Pos> {
+    pub(crate) labels: HashMap<P, usize>,
+    pub(crate) regions: Vec<HashSet<P>>,
+}
+
+impl<P: Eq + Hash> Default for Regions<P> {
+    fn default() -> Self {
+        Self {
+            labels: HashMap::new(),
+            regions: Vec::new(),
+        }
+    }
+}
+
+impl<P: Eq + Hash> Regions<P> {
+    /// The label of the region containing `pos`, or `None` if `pos` isn't passable.
+    #[must_use]
+    pub fn label(&self, pos: &P) -> Option<usize> {
+        self.labels.get(pos).copied()
+    }
+
+    /// The positions of the region with `label`.
+    #[must_use]
+    pub fn get(&self, label: usize) -> Option<&HashSet<P>> {
+        self.regions.get(label)
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// All regions, ordered by their label.
+    pub fn iter(&self) -> impl Iterator<Item = &HashSet<P>> {
+        self.regions.iter()
+    }
+}
@@ -3,9 +3,9 @@ use std::{
     str::FromStr,
 };
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::prelude::Diagonal;
+use crate::prelude::{Diagonal, HexDirection, Horizontal, LevelGlyph, Transform, Transformable};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Rock {
@@ -18,15 +18,162 @@ impl Display for Rock {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, strum::VariantNames,
+)]
 #[allow(clippy::module_name_repetitions)]
 pub enum RockKind {
+    #[default]
     Empty,
-    // TODO: This should probably be removed from here
-    // because it is not part of the map itself
+    /// Where a rolling rock starts. It is moved out of the map into the `MapState` when a level is loaded.
     RoundRock,
+    /// Where a rolling rock with a color starts, like a [`RockKind::RoundRock`]
+    ColoredRock(RockColor),
     SquareRock,
     SingleReflect(Diagonal),
+    /// Teleports rolling rocks to the other portal with the same letter or digit
+    Portal(char),
+    /// Lets rocks pass only in its direction and blocks them like a [`RockKind::SquareRock`] otherwise
+    Gate(Horizontal),
+    /// Deflects rolling rocks from all four directions
+    Mirror(Mirror),
+    /// Deflects rocks which roll against its flat side, and lets them pass along it
+    Splitter(Splitter),
+    /// Blocks rocks like a [`RockKind::SquareRock`], until rocks stopped against it as often as its hit points
+    CrackedWall(u8),
+    /// Blocks rocks while it is closed. Pressure plates and switches open and close it.
+    Door {
+        open: bool,
+    },
+}
+
+/// The color of a rock. Colored targets only count when a rock with their color rests on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RockColor {
+    Red,
+    Green,
+    Blue,
+}
+
+/// A double-sided mirror, named after its shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Mirror {
+    /// `/`
+    Rising,
+    /// `\`
+    Falling,
+}
+
+impl Mirror {
+    /// The direction in which a rock rolling in `direction` leaves the mirror.
+    #[must_use]
+    pub const fn reflect(self, direction: Horizontal) -> Horizontal {
+        match (self, direction) {
+            (Self::Rising, Horizontal::Right) | (Self::Falling, Horizontal::Left) => {
+                Horizontal::Top
+            }
+            (Self::Rising, Horizontal::Top) | (Self::Falling, Horizontal::Bottom) => {
+                Horizontal::Right
+            }
+            (Self::Rising, Horizontal::Left) | (Self::Falling, Horizontal::Right) => {
+                Horizontal::Bottom
+            }
+            (Self::Rising, Horizontal::Bottom) | (Self::Falling, Horizontal::Top) => {
+                Horizontal::Left
+            }
+        }
+    }
+
+    /// The direction in which a rock rolling diagonally in `direction` leaves the mirror.
+    /// A rock which rolls along the mirror passes it, and a rock which hits it head-on bounces back.
+    #[must_use]
+    pub const fn reflect_diagonal(self, direction: Diagonal) -> Diagonal {
+        match (self, direction) {
+            (Self::Rising, Diagonal::TopRight | Diagonal::BottomLeft)
+            | (Self::Falling, Diagonal::TopLeft | Diagonal::BottomRight) => direction,
+            _ => direction.opposite(),
+        }
+    }
+
+    /// The direction in which a rock rolling in `direction` on a hex grid leaves the mirror.
+    /// `/` lies along [`HexDirection::TopRight`] and `\` along [`HexDirection::TopLeft`],
+    /// and a rock which rolls along the mirror passes it.
+    #[must_use]
+    pub const fn reflect_hex(self, direction: HexDirection) -> HexDirection {
+        match (self, direction) {
+            (Self::Rising, HexDirection::Right) => HexDirection::TopLeft,
+            (Self::Rising, HexDirection::Left) => HexDirection::BottomRight,
+            (Self::Rising, HexDirection::TopLeft) | (Self::Falling, HexDirection::BottomLeft) => {
+                HexDirection::Right
+            }
+            (Self::Rising, HexDirection::BottomRight) | (Self::Falling, HexDirection::TopRight) => {
+                HexDirection::Left
+            }
+            (Self::Falling, HexDirection::Right) => HexDirection::BottomLeft,
+            (Self::Falling, HexDirection::Left) => HexDirection::TopRight,
+            _ => direction,
+        }
+    }
+}
+
+/// A splitter, named after the directions in which it sends the rocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Splitter {
+    /// `|`
+    TopBottom,
+    /// `-`
+    LeftRight,
+}
+
+impl Splitter {
+    /// The directions in which a rock rolling in `direction` leaves the splitter.
+    /// The rock itself turns clockwise, and its clone counterclockwise.
+    ///
+    /// Returns `None` if the rock rolls along the splitter and just passes it.
+    #[must_use]
+    pub fn split(self, direction: Horizontal) -> Option<(Horizontal, Horizontal)> {
+        let passes = match self {
+            Self::TopBottom => matches!(direction, Horizontal::Top | Horizontal::Bottom),
+            Self::LeftRight => matches!(direction, Horizontal::Left | Horizontal::Right),
+        };
+
+        (!passes).then(|| {
+            (
+                direction.transformed(Transform::RotateCw),
+                direction.transformed(Transform::RotateCcw),
+            )
+        })
+    }
+
+    /// The direction in which a rock rolling diagonally in `direction` leaves the splitter.
+    /// The splitter stops the part of the movement against its flat side, so the rock rolls along it.
+    #[must_use]
+    pub const fn deflect_diagonal(self, direction: Diagonal) -> Horizontal {
+        let [vertical, horizontal] = direction.horizontals();
+
+        match self {
+            Self::TopBottom => vertical,
+            Self::LeftRight => horizontal,
+        }
+    }
+
+    /// The directions in which a rock rolling in `direction` on a hex grid leaves the splitter, like [`Splitter::split`].
+    /// The rock keeps its side and its clone goes the other way.
+    ///
+    /// Only `-` lies along hex directions, so `|` never splits rocks.
+    /// Returns `None` if the rock rolls along the splitter or the splitter is a `|`.
+    #[must_use]
+    pub const fn split_hex(self, direction: HexDirection) -> Option<(HexDirection, HexDirection)> {
+        match (self, direction) {
+            (Self::LeftRight, HexDirection::TopRight | HexDirection::BottomRight) => {
+                Some((HexDirection::Right, HexDirection::Left))
+            }
+            (Self::LeftRight, HexDirection::TopLeft | HexDirection::BottomLeft) => {
+                Some((HexDirection::Left, HexDirection::Right))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl FromStr for RockKind {
@@ -36,21 +183,74 @@ impl FromStr for RockKind {
         Ok(match s {
             "." => Self::Empty,
             "o" => Self::RoundRock,
+            "r" => Self::ColoredRock(RockColor::Red),
+            "g" => Self::ColoredRock(RockColor::Green),
+            "b" => Self::ColoredRock(RockColor::Blue),
             "#" => Self::SquareRock,
             "◢" => Self::SingleReflect(Diagonal::TopLeft),
             "◣" => Self::SingleReflect(Diagonal::TopRight),
             "◥" => Self::SingleReflect(Diagonal::BottomLeft),
             "◤" => Self::SingleReflect(Diagonal::BottomRight),
-            _ => return Err(format!("{s} is not a RockKind")),
+            "↑" => Self::Gate(Horizontal::Top),
+            "←" => Self::Gate(Horizontal::Left),
+            "→" => Self::Gate(Horizontal::Right),
+            "↓" => Self::Gate(Horizontal::Bottom),
+            "/" | "╱" => Self::Mirror(Mirror::Rising),
+            "\\" | "╲" => Self::Mirror(Mirror::Falling),
+            "|" | "│" => Self::Splitter(Splitter::TopBottom),
+            "-" | "─" => Self::Splitter(Splitter::LeftRight),
+            "&" | "▮" => Self::Door { open: false },
+            "'" | "▯" => Self::Door { open: true },
+            _ => match s.chars().collect::<Vec<_>>()[..] {
+                [id] if id.is_ascii_uppercase() || id.is_ascii_digit() => Self::Portal(id),
+                ['%', hit_points @ '1'..='9'] => Self::CrackedWall(hit_points as u8 - b'0'),
+                _ => return Err(format!("{s} is not a RockKind")),
+            },
         })
     }
 }
 
+impl LevelGlyph for RockKind {
+    fn write_level_glyph(&self, f: &mut impl Write) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_char('.'),
+            Self::RoundRock => f.write_char('o'),
+            Self::ColoredRock(color) => f.write_char(match color {
+                RockColor::Red => 'r',
+                RockColor::Green => 'g',
+                RockColor::Blue => 'b',
+            }),
+            Self::SquareRock => f.write_char('#'),
+            Self::SingleReflect(direction) => f.write_str(match direction {
+                Diagonal::TopLeft => "◢",
+                Diagonal::TopRight => "◣",
+                Diagonal::BottomLeft => "◥",
+                Diagonal::BottomRight => "◤",
+            }),
+            Self::Portal(id) => f.write_char(*id),
+            Self::Gate(direction) => f.write_str(match direction {
+                Horizontal::Top => "↑",
+                Horizontal::Left => "←",
+                Horizontal::Right => "→",
+                Horizontal::Bottom => "↓",
+            }),
+            Self::Mirror(Mirror::Rising) => f.write_char('/'),
+            Self::Mirror(Mirror::Falling) => f.write_char('\\'),
+            Self::Splitter(Splitter::TopBottom) => f.write_char('|'),
+            Self::Splitter(Splitter::LeftRight) => f.write_char('-'),
+            Self::CrackedWall(hit_points) => write!(f, "%{hit_points}"),
+            Self::Door { open: false } => f.write_char('&'),
+            Self::Door { open: true } => f.write_char('\''),
+        }
+    }
+}
+
 impl Display for RockKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Empty => f.write_char('•'),
             Self::RoundRock => f.write_char('○'),
+            Self::ColoredRock(_) => f.write_char('●'),
             Self::SquareRock => f.write_char('▨'),
             Self::SingleReflect(direction) => f.write_str(match direction {
                 Diagonal::TopLeft => "◢",
@@ -58,12 +258,37 @@ impl Display for RockKind {
                 Diagonal::BottomLeft => "◥",
                 Diagonal::BottomRight => "◤",
             }),
+            Self::Portal(id) => f.write_char(portal_display_glyph(*id)),
+            Self::Gate(direction) => f.write_str(match direction {
+                Horizontal::Top => "↑",
+                Horizontal::Left => "←",
+                Horizontal::Right => "→",
+                Horizontal::Bottom => "↓",
+            }),
+            Self::Mirror(Mirror::Rising) => f.write_char('╱'),
+            Self::Mirror(Mirror::Falling) => f.write_char('╲'),
+            Self::Splitter(Splitter::TopBottom) => f.write_char('│'),
+            Self::Splitter(Splitter::LeftRight) => f.write_char('─'),
+            Self::CrackedWall(hit_points) => f.write_char(match hit_points {
+                0 => '•',
+                1 => '░',
+                2 => '▒',
+                _ => '▓',
+            }),
+            Self::Door { open: false } => f.write_char('▮'),
+            Self::Door { open: true } => f.write_char('▯'),
         }
     }
 }
 
-impl Default for RockKind {
-    fn default() -> Self {
-        Self::Empty
-    }
+/// The circled version of the portal letter or digit, like `Ⓐ` or `①`.
+fn portal_display_glyph(id: char) -> char {
+    let circled = match id {
+        'A'..='Z' => Some(0x24B6 + (id as u32 - 'A' as u32)),
+        '1'..='9' => Some(0x2460 + (id as u32 - '1' as u32)),
+        '0' => Some(0x24EA),
+        _ => None,
+    };
+
+    circled.and_then(char::from_u32).unwrap_or(id)
 }
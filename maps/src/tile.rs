@@ -1,30 +1,275 @@
-use std::{fmt::Display, str::FromStr};
+use std::{
+    fmt::{Display, Write},
+    str::FromStr,
+};
 
-use serde::Deserialize;
+use serde::{
+    de::{self, value::StringDeserializer, EnumAccess, IntoDeserializer, VariantAccess, Visitor},
+    ser::SerializeStructVariant,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use strum::VariantNames;
 
-use crate::prelude::RockKind;
+use crate::prelude::{Floor, LevelGlyph, RockKind};
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+/// A cell of the map, made of two layers: the `floor` and the `object` standing on it.
+///
+/// With serde a tile is written by the name of its only layer, like `RoundRock` or `Target`,
+/// and a tile with both layers as `Tile(floor: Target, object: RoundRock)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Tile {
-    pub rock: RockKind,
+    pub floor: Floor,
+    pub object: RockKind,
 }
 
 impl FromStr for Tile {
     type Err = <RockKind as FromStr>::Err;
 
+    /// A tile is either a glyph for its object or its floor,
+    /// or a floor glyph directly followed by an object glyph, like `+o`.
     fn from_str(s: &str) -> Result<Self, <RockKind as FromStr>::Err> {
-        <RockKind as FromStr>::from_str(s).map(Self::from)
+        let err = match <RockKind as FromStr>::from_str(s) {
+            Ok(object) => return Ok(Self::from(object)),
+            Err(err) => err,
+        };
+
+        let mut chars = s.chars();
+
+        let (Some(first), rest) = (chars.next(), chars.as_str()) else {
+            return Err(err);
+        };
+
+        if rest.is_empty() {
+            return <Floor as FromStr>::from_str(s)
+                .map(Self::from)
+                .map_err(|_| err);
+        }
+
+        Ok(Self {
+            floor: <Floor as FromStr>::from_str(&s[..first.len_utf8()])?,
+            object: <RockKind as FromStr>::from_str(rest)?,
+        })
     }
 }
 
 impl Display for Tile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        <RockKind as Display>::fmt(&self.rock, f)
+        match self.object {
+            RockKind::Empty => <Floor as Display>::fmt(&self.floor, f),
+            _ => <RockKind as Display>::fmt(&self.object, f),
+        }
+    }
+}
+
+impl LevelGlyph for Tile {
+    fn write_level_glyph(&self, f: &mut impl Write) -> std::fmt::Result {
+        match (self.floor, self.object) {
+            (Floor::Normal, object) => object.write_level_glyph(f),
+            (floor, RockKind::Empty) => floor.write_level_glyph(f),
+            (floor, object) => {
+                floor.write_level_glyph(f)?;
+                object.write_level_glyph(f)
+            }
+        }
     }
 }
 
 impl From<RockKind> for Tile {
     fn from(value: RockKind) -> Self {
-        Self { rock: value }
+        Self {
+            floor: Floor::default(),
+            object: value,
+        }
+    }
+}
+
+impl From<Floor> for Tile {
+    fn from(value: Floor) -> Self {
+        Self {
+            floor: value,
+            object: RockKind::default(),
+        }
+    }
+}
+
+impl Serialize for Tile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self.floor, self.object) {
+            (Floor::Normal, object) => object.serialize(serializer),
+            (floor, RockKind::Empty) => floor.serialize(serializer),
+            (floor, object) => {
+                let mut layers = serializer.serialize_struct_variant("Tile", 0, "Tile", 2)?;
+                layers.serialize_field("floor", &floor)?;
+                layers.serialize_field("object", &object)?;
+                layers.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Tile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // The formats only use the variant names for error messages, the names of the floors are accepted as well
+        deserializer.deserialize_enum("Tile", RockKind::VARIANTS, TileVisitor)
+    }
+}
+
+/// Both layers of a tile, for the `Tile(floor: .., object: ..)` form.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Layers {
+    #[serde(default)]
+    floor: Floor,
+    #[serde(default)]
+    object: RockKind,
+}
+
+struct TileVisitor;
+
+impl<'de> Visitor<'de> for TileVisitor {
+    type Value = Tile;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("the name of a RockKind or a Floor, or a Tile with both layers")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let (TileName(name), variant) = data.variant()?;
+
+        if RockKind::VARIANTS.contains(&name.as_str()) {
+            RockKind::deserialize(NamedVariant { name, variant }).map(Tile::from)
+        } else if Floor::VARIANTS.contains(&name.as_str()) {
+            Floor::deserialize(NamedVariant { name, variant }).map(Tile::from)
+        } else if name == "Tile" {
+            Layers::deserialize(NamedVariant { name, variant }).map(|layers| Tile {
+                floor: layers.floor,
+                object: layers.object,
+            })
+        } else {
+            Err(de::Error::custom(format!(
+                "unknown tile `{name}`, expected the name of a RockKind or a Floor, or `Tile`"
+            )))
+        }
+    }
+}
+
+/// The variant name of a tile, which most formats only hand out as an identifier.
+struct TileName(String);
+
+impl<'de> Deserialize<'de> for TileName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(TileNameVisitor)
+    }
+}
+
+struct TileNameVisitor;
+
+impl Visitor<'_> for TileNameVisitor {
+    type Value = TileName;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a tile name")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(TileName(v.to_owned()))
+    }
+}
+
+/// An enum variant whose name was already read, so it can be handed to the `Deserialize` impl of the matching type.
+struct NamedVariant<A> {
+    name: String,
+    variant: A,
+}
+
+impl<'de, A: VariantAccess<'de>> Deserializer<'de> for NamedVariant<A> {
+    type Error = A::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.variant.struct_variant(fields, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> EnumAccess<'de> for NamedVariant<A> {
+    type Error = A::Error;
+    type Variant = A;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let name: StringDeserializer<A::Error> = self.name.into_deserializer();
+
+        Ok((seed.deserialize(name)?, self.variant))
+    }
+}
+
+#[cfg(test)]
+mod tile_tests {
+    use crate::prelude::Diagonal;
+
+    use super::*;
+
+    #[test]
+    fn parse_layers() {
+        assert_eq!(Tile::from_str("o"), Ok(Tile::from(RockKind::RoundRock)));
+        assert_eq!(Tile::from_str("+"), Ok(Tile::from(Floor::Target)));
+        assert_eq!(
+            Tile::from_str("+◢"),
+            Ok(Tile {
+                floor: Floor::Target,
+                object: RockKind::SingleReflect(Diagonal::TopLeft),
+            })
+        );
+        assert_eq!(Tile::from_str(".#"), Ok(Tile::from(RockKind::SquareRock)));
+        assert!(Tile::from_str("+x").is_err());
+        assert!(Tile::from_str("o+").is_err());
+    }
+
+    #[test]
+    fn level_glyph_round_trip() {
+        for glyph in [
+            ".", "o", "+", "+o", "+#", "+◤", "A", "+7", "%3", "+%1", "~→", "⇐o",
+        ] {
+            let tile = Tile::from_str(glyph).expect("Tile should parse");
+            assert_eq!(tile.level_glyph(), glyph);
+        }
+    }
+
+    #[test]
+    fn serde_names() {
+        for (name, glyph) in [
+            ("Empty", "."),
+            ("RoundRock", "o"),
+            ("Target", "+"),
+            ("SingleReflect(TopLeft)", "◢"),
+            ("Door(open:true)", "'"),
+            ("Tile(floor:Target,object:RoundRock)", "+o"),
+        ] {
+            let tile = ron::from_str::<Tile>(name).expect("Tile should deserialize");
+            assert_eq!(Ok(&tile), Tile::from_str(glyph).as_ref());
+            assert_eq!(ron::to_string(&tile).as_deref(), Ok(name));
+        }
+
+        assert_eq!(
+            serde_json::from_str::<Tile>(r#"{"Tile":{"floor":"Ice"}}"#).ok(),
+            Some(Tile::from(Floor::Ice))
+        );
+        assert!(ron::from_str::<Tile>("Lava").is_err());
     }
 }
@@ -0,0 +1,257 @@
+use crate::prelude::{
+    Diagonal, Direction, Floor, Horizontal, Mirror, Pos, RockKind, Splitter, Tile, Wrap,
+};
+
+/// A rotation or reflection of a whole map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize)]
+pub enum Transform {
+    RotateCw,
+    RotateCcw,
+    FlipHorizontal,
+    FlipVertical,
+    Transpose,
+}
+
+impl Transform {
+    #[must_use]
+    pub const fn inverse(self) -> Self {
+        match self {
+            Self::RotateCw => Self::RotateCcw,
+            Self::RotateCcw => Self::RotateCw,
+            Self::FlipHorizontal | Self::FlipVertical | Self::Transpose => self,
+        }
+    }
+
+    /// Returns `(width, height)` of a map with the given size after the transformation.
+    #[must_use]
+    pub const fn map_size(self, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            Self::RotateCw | Self::RotateCcw | Self::Transpose => (height, width),
+            Self::FlipHorizontal | Self::FlipVertical => (width, height),
+        }
+    }
+
+    /// Maps a position of a map with the given size to its position after the transformation.
+    #[must_use]
+    pub const fn map_pos(self, pos: Pos, width: u32, height: u32) -> Pos {
+        match self {
+            Self::RotateCw => Pos::new(height - 1 - pos.y, pos.x),
+            Self::RotateCcw => Pos::new(pos.y, width - 1 - pos.x),
+            Self::FlipHorizontal => Pos::new(width - 1 - pos.x, pos.y),
+            Self::FlipVertical => Pos::new(pos.x, height - 1 - pos.y),
+            Self::Transpose => Pos::new(pos.y, pos.x),
+        }
+    }
+}
+
+/// Values which have an orientation that changes when the map they are on gets transformed.
+pub trait Transformable {
+    #[must_use]
+    fn transformed(self, transform: Transform) -> Self;
+}
+
+macro_rules! impl_transformable_without_orientation {
+    ($($ty: ty),*) => {
+        $(
+            impl Transformable for $ty {
+                fn transformed(self, _transform: Transform) -> Self {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+impl_transformable_without_orientation!(bool, char, u8, u16, u32, u64, usize, i32, String);
+
+impl<T: Transformable> Transformable for Option<T> {
+    fn transformed(self, transform: Transform) -> Self {
+        self.map(|value| value.transformed(transform))
+    }
+}
+
+impl Transformable for Horizontal {
+    fn transformed(self, transform: Transform) -> Self {
+        match transform {
+            Transform::RotateCw => match self {
+                Self::Top => Self::Right,
+                Self::Right => Self::Bottom,
+                Self::Bottom => Self::Left,
+                Self::Left => Self::Top,
+            },
+            Transform::RotateCcw => match self {
+                Self::Top => Self::Left,
+                Self::Left => Self::Bottom,
+                Self::Bottom => Self::Right,
+                Self::Right => Self::Top,
+            },
+            Transform::FlipHorizontal => match self {
+                Self::Left => Self::Right,
+                Self::Right => Self::Left,
+                Self::Top | Self::Bottom => self,
+            },
+            Transform::FlipVertical => match self {
+                Self::Top => Self::Bottom,
+                Self::Bottom => Self::Top,
+                Self::Left | Self::Right => self,
+            },
+            Transform::Transpose => match self {
+                Self::Top => Self::Left,
+                Self::Left => Self::Top,
+                Self::Bottom => Self::Right,
+                Self::Right => Self::Bottom,
+            },
+        }
+    }
+}
+
+impl Transformable for Diagonal {
+    fn transformed(self, transform: Transform) -> Self {
+        let [first, second] = self.horizontals();
+
+        Self::from_horizontals(first.transformed(transform), second.transformed(transform))
+            .expect("transforming two perpendicular directions keeps them perpendicular")
+    }
+}
+
+impl Transformable for Direction {
+    fn transformed(self, transform: Transform) -> Self {
+        match self {
+            Self::Straight(horizontal) => Self::Straight(horizontal.transformed(transform)),
+            Self::Diagonal(diagonal) => Self::Diagonal(diagonal.transformed(transform)),
+        }
+    }
+}
+
+impl Transformable for Mirror {
+    fn transformed(self, transform: Transform) -> Self {
+        match (transform, self) {
+            (Transform::Transpose, _) => self,
+            (_, Self::Rising) => Self::Falling,
+            (_, Self::Falling) => Self::Rising,
+        }
+    }
+}
+
+impl Transformable for Splitter {
+    fn transformed(self, transform: Transform) -> Self {
+        match (transform, self) {
+            (Transform::FlipHorizontal | Transform::FlipVertical, _) => self,
+            (_, Self::TopBottom) => Self::LeftRight,
+            (_, Self::LeftRight) => Self::TopBottom,
+        }
+    }
+}
+
+impl Transformable for RockKind {
+    fn transformed(self, transform: Transform) -> Self {
+        match self {
+            Self::Empty
+            | Self::RoundRock
+            | Self::ColoredRock(_)
+            | Self::SquareRock
+            | Self::Portal(_)
+            | Self::CrackedWall(_)
+            | Self::Door { .. } => self,
+            Self::SingleReflect(diagonal) => Self::SingleReflect(diagonal.transformed(transform)),
+            Self::Gate(direction) => Self::Gate(direction.transformed(transform)),
+            Self::Mirror(mirror) => Self::Mirror(mirror.transformed(transform)),
+            Self::Splitter(splitter) => Self::Splitter(splitter.transformed(transform)),
+        }
+    }
+}
+
+impl Transformable for Floor {
+    fn transformed(self, transform: Transform) -> Self {
+        match self {
+            Self::Conveyor(direction) => Self::Conveyor(direction.transformed(transform)),
+            _ => self,
+        }
+    }
+}
+
+impl Transformable for Wrap {
+    fn transformed(self, transform: Transform) -> Self {
+        match (transform, self) {
+            (Transform::FlipHorizontal | Transform::FlipVertical, _)
+            | (_, Self::Off | Self::Both) => self,
+            (_, Self::Horizontal) => Self::Vertical,
+            (_, Self::Vertical) => Self::Horizontal,
+        }
+    }
+}
+
+impl Transformable for Tile {
+    fn transformed(self, transform: Transform) -> Self {
+        Self {
+            floor: self.floor.transformed(transform),
+            object: self.object.transformed(transform),
+        }
+    }
+}
+
+#[cfg(test)]
+mod transform_tests {
+    use super::*;
+
+    const ALL_TRANSFORMS: [Transform; 5] = [
+        Transform::RotateCw,
+        Transform::RotateCcw,
+        Transform::FlipHorizontal,
+        Transform::FlipVertical,
+        Transform::Transpose,
+    ];
+
+    #[test]
+    fn inverse_restores_pos() {
+        for transform in ALL_TRANSFORMS {
+            let (width, height) = transform.map_size(3, 5);
+
+            for pos in [Pos::new(0, 0), Pos::new(2, 1), Pos::new(1, 4)] {
+                assert_eq!(
+                    transform
+                        .inverse()
+                        .map_pos(transform.map_pos(pos, 3, 5), width, height),
+                    pos,
+                    "{transform:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rotate_reflectors() {
+        assert_eq!(
+            Diagonal::TopLeft.transformed(Transform::RotateCw),
+            Diagonal::TopRight
+        );
+        assert_eq!(
+            Diagonal::TopLeft.transformed(Transform::RotateCcw),
+            Diagonal::BottomLeft
+        );
+        assert_eq!(
+            Diagonal::TopLeft.transformed(Transform::FlipHorizontal),
+            Diagonal::TopRight
+        );
+        assert_eq!(
+            Diagonal::TopLeft.transformed(Transform::FlipVertical),
+            Diagonal::BottomLeft
+        );
+        assert_eq!(
+            Diagonal::TopRight.transformed(Transform::Transpose),
+            Diagonal::BottomLeft
+        );
+
+        for diagonal in [
+            Diagonal::TopLeft,
+            Diagonal::TopRight,
+            Diagonal::BottomLeft,
+            Diagonal::BottomRight,
+        ] {
+            let rotated = (0..4).fold(diagonal, |diagonal, _| {
+                diagonal.transformed(Transform::RotateCw)
+            });
+            assert_eq!(rotated, diagonal);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prelude::{Direction, Grid, Map, Pos, Tile},
+    W,
+};
+
+/// Which edges of a map lead to the opposite edge, like on a torus.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Wrap {
+    #[default]
+    Off,
+    /// The left and right edges are connected
+    Horizontal,
+    /// The top and bottom edges are connected
+    Vertical,
+    Both,
+}
+
+impl Wrap {
+    #[must_use]
+    pub const fn wraps_x(self) -> bool {
+        matches!(self, Self::Horizontal | Self::Both)
+    }
+
+    #[must_use]
+    pub const fn wraps_y(self) -> bool {
+        matches!(self, Self::Vertical | Self::Both)
+    }
+}
+
+/// A map seen through its wrapping edges, so the neighbors of the border tiles can lie on the opposite edge.
+/// Its neighbours lie in all eight directions, so rocks can roll diagonally on it.
+pub struct WrappedMap<'a, T = Tile> {
+    pub map: &'a Map<T>,
+    pub wrap: Wrap,
+}
+
+impl<T> Grid for WrappedMap<'_, T> {
+    type Pos = Pos;
+    type Direction = Direction;
+    type Item = T;
+
+    const DIRECTIONS: &'static [Direction] = &Direction::ALL;
+
+    fn size(&self) -> Pos {
+        self.map.size()
+    }
+
+    fn get(&self, pos: &Pos) -> Option<&T> {
+        self.map.get(pos)
+    }
+
+    fn neighbor(&self, pos: &Pos, direction: Direction) -> Option<Pos> {
+        W(pos).try_add_in_wrapped_map(self.map, &direction.to_offset(), self.wrap)
+    }
+
+    fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.map.all_pos()
+    }
+}
@@ -1,46 +1,189 @@
-use std::collections::HashSet;
-
 use classes::Level;
-use game_classes::{MapData, MapState};
-use maps::prelude::{Map, RockKind};
+use game_classes::{MapData, MapState, RockWinConditions};
+use maps::prelude::{Floor, RockKind};
+use ron::{error::SpannedResult, extensions::Extensions};
 
-pub fn load_map_data(level: Level) -> (MapData, MapState) {
-    let data = level.get_data();
+use crate::{Error, Result};
 
-    let map_data = ron::from_str::<MapData>(data);
+pub fn load_map_data(level: Level) -> Result<(MapData, MapState)> {
+    let data = level.get_data();
 
-    let mut map_data = map_data.unwrap_or_else(|_| panic!("Should load level {level:?}"));
+    let mut map_data =
+        parse_map_data(data).map_err(|source| Error::InvalidLevel { level, source })?;
 
     let initial_state = prepare_map(&mut map_data);
 
-    (map_data, initial_state)
+    Ok((map_data, initial_state))
+}
+
+/// Reads a level file, where the `implicit_some` extension allows bare tile names in maps written as rows,
+/// like `[[Empty, RoundRock], [Target, None]]`.
+pub fn parse_map_data(data: &str) -> SpannedResult<MapData> {
+    ron::Options::default()
+        .with_default_extension(Extensions::IMPLICIT_SOME)
+        .from_str(data)
 }
 
 pub fn prepare_map(map_data: &mut MapData) -> MapState {
-    map_data.map = Map::new(
-        map_data
-            .map
-            .rows()
-            .filter(|row| row.clone().next().is_some())
-            .map(std::iter::Iterator::cloned),
-    );
-
-    let mut initial_state = MapState {
-        rock_positions: HashSet::new(),
-    };
-
-    let all_pos = map_data.map.all_pos().copied().collect::<Vec<_>>();
-    for pos in all_pos {
-        let Some(tile) = map_data.map.get_mut(&pos) else {
-            continue;
-        };
+    let mut initial_state = MapState::default();
+
+    for (pos, tile) in &mut map_data.map {
+        if tile.object == (RockKind::Door { open: true }) {
+            initial_state.open_doors.insert(pos);
+        }
 
-        if tile.rock == RockKind::RoundRock {
-            tile.rock = RockKind::Empty;
+        if let RockKind::RoundRock | RockKind::ColoredRock(_) = tile.object {
+            if let RockKind::ColoredRock(color) = tile.object {
+                initial_state.rock_colors.insert(pos, color);
+            }
+            tile.object = RockKind::Empty;
 
             assert!(initial_state.rock_positions.insert(pos));
         }
+
+        if tile.floor == Floor::Target {
+            if let RockWinConditions::Pos(win_pos) = &mut map_data.win.rocks {
+                if !win_pos.contains(&pos) {
+                    win_pos.push(pos);
+                }
+            }
+        }
     }
 
     initial_state
 }
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashSet, str::FromStr};
+
+    use game_classes::{GeneralWinConditions, Link, WinCondition};
+    use maps::prelude::{Map, Pos, URect};
+    use strum::VariantNames;
+
+    use super::*;
+
+    #[test]
+    fn prepare_layered_tiles() {
+        let mut map_data = MapData::new(
+            Map::from_str("o +\n+o .").expect("Map should parse"),
+            WinCondition {
+                general: GeneralWinConditions::default(),
+                rocks: RockWinConditions::Pos(vec![Pos::new(0, 1)]),
+            },
+            Vec::new(),
+        );
+
+        let state = prepare_map(&mut map_data);
+
+        assert_eq!(
+            state.rock_positions,
+            HashSet::from([Pos::new(0, 0), Pos::new(0, 1)])
+        );
+        assert_eq!(map_data.map.to_level_string(), ". +\n+ .\n");
+        assert!(matches!(
+            map_data.win.rocks,
+            RockWinConditions::Pos(win_pos) if win_pos == vec![Pos::new(0, 1), Pos::new(1, 0)]
+        ));
+    }
+
+    #[test]
+    fn load_all_levels() {
+        for level in Level::VARIANTS {
+            let level = level.parse().expect("Level names should parse");
+
+            if let Err(err) = load_map_data(level) {
+                panic!("{err}");
+            }
+        }
+    }
+
+    #[test]
+    fn serialize_levels_round_trip() {
+        for level in Level::VARIANTS {
+            let level: Level = level.parse().expect("Level names should parse");
+
+            let map_data = parse_map_data(level.get_data()).expect("Level should deserialize");
+            let serialized = ron::to_string(&map_data).expect("Level should serialize");
+            let reloaded = parse_map_data(&serialized).expect("Serialized level should load again");
+
+            assert_eq!(map_data.map, reloaded.map, "{level:?}");
+            assert_eq!(
+                serialized,
+                ron::to_string(&reloaded).expect("Level should serialize"),
+                "{level:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn load_levels_with_structured_maps() {
+        let level = |map| {
+            parse_map_data(&format!(
+                "(map: {map}, win: (general: (max_moves: None), rocks: Pos([(2, 0)])))"
+            ))
+            .map(|map_data| map_data.map.to_level_string())
+            .expect("Level should deserialize")
+        };
+
+        assert_eq!(
+            level("[[RoundRock, SquareRock, Target], [Empty, None, None]]"),
+            "o # +\n. _ _\n"
+        );
+        assert_eq!(
+            level("[[Some(RoundRock), Some(SquareRock), Some(Target)], [Some(Empty), None, None]]"),
+            "o # +\n. _ _\n"
+        );
+        assert_eq!(
+            level("(width: 3, height: 2, cells: [((0, 0), RoundRock), ((1, 0), SquareRock), ((2, 0), Target)], void: [(1, 1), (2, 1)])"),
+            "o # +\n. _ _\n"
+        );
+        assert!(
+            parse_map_data(
+                "(map: [[Portal('A')]], win: (general: (max_moves: None), rocks: Pos([])))"
+            )
+            .is_err(),
+            "portals are validated in every format"
+        );
+    }
+
+    #[test]
+    fn crop_level_with_its_rocks() {
+        let mut map_data = MapData::new(
+            Map::from_str("o . . +\n. & o .\n. . . =").expect("Map should parse"),
+            WinCondition {
+                general: GeneralWinConditions::default(),
+                rocks: RockWinConditions::Pos(Vec::new()),
+            },
+            vec![Link {
+                trigger: Pos::new(3, 2),
+                doors: vec![Pos::new(1, 1)],
+            }],
+        );
+        let state = prepare_map(&mut map_data);
+
+        let rect = URect::new(1, 0, 4, 3);
+        let cropped = map_data.crop(rect);
+        let cropped_state = state.shift(-rect.min.as_ivec2(), 3, 3);
+
+        assert_eq!(cropped.map.to_level_string(), ". . +\n& . .\n. . =\n");
+        assert_eq!(
+            cropped_state.rock_positions,
+            HashSet::from([Pos::new(1, 1)])
+        );
+        assert!(matches!(
+            &cropped.win.rocks,
+            RockWinConditions::Pos(win_pos) if *win_pos == vec![Pos::new(2, 0)]
+        ));
+        assert_eq!(
+            cropped.links,
+            vec![Link {
+                trigger: Pos::new(2, 2),
+                doors: vec![Pos::new(0, 1)],
+            }]
+        );
+
+        let padded = cropped.pad(1, None);
+        assert_eq!(padded.links[0].trigger, Pos::new(3, 3));
+    }
+}
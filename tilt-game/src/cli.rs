@@ -5,7 +5,7 @@ use console::{style, Term};
 use strum::VariantNames;
 
 use classes::{Level, RoundResult};
-use maps::prelude::{Diagonal, RockKind};
+use maps::prelude::{Diagonal, Floor, Horizontal, Mirror, RockColor, RockKind, Splitter};
 
 use crate::game::setting;
 
@@ -49,10 +49,14 @@ enum LevelCommands {
 
     /// Load a Level with the given Name
     Load { level: String },
+
+    /// Check that every target of the Level with the given Name can be reached by a rock
+    Check { level: String },
 }
 
 pub enum Action {
     LoadLevel(String),
+    CheckLevel(String),
     Result(RoundResult),
     RestartLevel,
     Quit,
@@ -83,12 +87,13 @@ pub fn parse_cmd(term: &Term) -> Result<Option<Action>> {
         Commands::Level(cmd) => match cmd {
             LevelCommands::List => term.write_line(Level::VARIANTS.join("\n").as_str())?,
             LevelCommands::Load { level } => return Ok(Some(Action::LoadLevel(level))),
+            LevelCommands::Check { level } => return Ok(Some(Action::CheckLevel(level))),
         },
         Commands::Settings { list: _ } => {
             term.write_line(&format!("{:?}", setting()))?;
         }
         Commands::Help => write_help_text(term)?,
-    };
+    }
 
     Ok(None)
 }
@@ -108,11 +113,29 @@ Move the rocks around to win!
 Rock Types
 - empty Space ({0})
 - round, rolling Rocks ({1})
+- colored Rocks ({32}), which have to rest on Targets of their Color
 - solid, non moving Rocks ({2})
 - direction changing Corners ({3}, {4}, {5}, {6})
+- Portals, which teleport Rocks to the Portal with the same Letter ({8})
+- one-way Gates, which Rocks can only pass in the Direction of the Arrow ({13}, {14}, {15}, {16})
+- Mirrors, which deflect Rocks from all Sides ({21}, {22})
+- Splitters, which deflect Rocks rolling against them ({23}, {24})
+- cracked Walls, which break after Rocks stopped against them as often as their Hit Points ({25}, {26}, {27})
+- Doors, which are closed ({28}) or open ({29}) until Pressure Plates or Switches move them
+
+Floor Types
+- normal Floor ({0})
+- Targets, which need a Rock on them ({7})
+- Ice, on which Rocks keep sliding ({9})
+- Sand, which stops every Rock rolling onto it ({10})
+- Pits, which swallow Rocks ({11}), or only the first one before they are filled ({12})
+- Conveyors, which push resting Rocks one Tile after every Tilt ({17}, {18}, {19}, {20})
+- Pressure Plates, which move their Doors while a Rock rests on them ({30})
+- Switches, which move their Doors every time a Rock rolls over them ({31})
 
 Controls:
 Arrow or WASD Keys => move Rocks / tilt Platform
+Q, E, Z, C => tilt Platform diagonally, in Levels which allow it
 Escape => quit the game
 h, ? => help
 : => CLI
@@ -124,5 +147,31 @@ h, ? => help
         RockKind::SingleReflect(Diagonal::BottomRight),
         RockKind::SingleReflect(Diagonal::TopLeft),
         RockKind::SingleReflect(Diagonal::TopRight),
+        Floor::Target,
+        RockKind::Portal('A'),
+        Floor::Ice,
+        Floor::Sand,
+        Floor::Pit,
+        Floor::ShallowPit,
+        RockKind::Gate(Horizontal::Top),
+        RockKind::Gate(Horizontal::Left),
+        RockKind::Gate(Horizontal::Right),
+        RockKind::Gate(Horizontal::Bottom),
+        Floor::Conveyor(Horizontal::Top),
+        Floor::Conveyor(Horizontal::Left),
+        Floor::Conveyor(Horizontal::Right),
+        Floor::Conveyor(Horizontal::Bottom),
+        RockKind::Mirror(Mirror::Rising),
+        RockKind::Mirror(Mirror::Falling),
+        RockKind::Splitter(Splitter::TopBottom),
+        RockKind::Splitter(Splitter::LeftRight),
+        RockKind::CrackedWall(1),
+        RockKind::CrackedWall(2),
+        RockKind::CrackedWall(3),
+        RockKind::Door { open: false },
+        RockKind::Door { open: true },
+        Floor::PressurePlate,
+        Floor::Switch,
+        RockKind::ColoredRock(RockColor::Red),
     ))
 }
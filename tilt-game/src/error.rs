@@ -1,5 +1,7 @@
 use std::io;
 
+use classes::Level;
+
 use crate::cli::CmdError;
 
 #[derive(thiserror::Error, Debug)]
@@ -7,6 +9,12 @@ pub enum Error {
     #[error("Level {0} not found")]
     LevelNotFound(String),
 
+    #[error("Level {level:?} could not be loaded: {source}")]
+    InvalidLevel {
+        level: Level,
+        source: ron::error::SpannedError,
+    },
+
     #[error(transparent)]
     Io(#[from] io::Error),
 
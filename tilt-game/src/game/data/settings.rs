@@ -1,8 +1,7 @@
-use std::time::Duration;
+use std::{sync::LazyLock, time::Duration};
 
 use config::{Config, File};
 use directories::ProjectDirs;
-use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -32,10 +31,10 @@ impl Settings {
     }
 }
 
-pub(super) static PROJECT_DIR: Lazy<Option<ProjectDirs>> =
-    Lazy::new(|| ProjectDirs::from("", "", "tilt-game"));
+pub(super) static PROJECT_DIR: LazyLock<Option<ProjectDirs>> =
+    LazyLock::new(|| ProjectDirs::from("", "", "tilt-game"));
 
-static SETTINGS: Lazy<Settings> = Lazy::new(|| {
+static SETTINGS: LazyLock<Settings> = LazyLock::new(|| {
     if cfg!(test) {
         Settings::default()
     } else {
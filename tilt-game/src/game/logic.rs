@@ -1,3 +1,5 @@
+use std::fmt::Write;
+
 use console::Term;
 
 use classes::RoundStats;
@@ -9,6 +11,8 @@ mod input;
 mod tilt;
 mod winning;
 
+pub(super) use tilt::{stranded_rocks, unreachable_targets};
+
 pub fn print_map(
     term: &Term,
     map_data: &MapData,
@@ -19,7 +23,7 @@ pub fn print_map(
     let mut display_infos = String::new();
 
     if let Some(max_moves) = map_data.win.general.max_moves {
-        display_infos += &format!("Move {} of {}", round_stats.moves, max_moves);
+        let _ = write!(display_infos, "Move {} of {}", round_stats.moves, max_moves);
     }
 
     let mut parts = vec![display_map, display_infos];
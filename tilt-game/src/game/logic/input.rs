@@ -18,20 +18,32 @@ pub(super) fn handle_input(
     state: &mut MapState,
     round_stats: &mut RoundStats,
 ) -> Result<Option<Action>> {
-    let mut rotate_towards = None::<Horizontal>;
+    let mut rotate_towards = None::<Direction>;
 
     match input {
         Key::Char('w') | Key::ArrowUp => {
-            rotate_towards = Some(Horizontal::Top);
+            rotate_towards = Some(Horizontal::Top.into());
         }
         Key::Char('a') | Key::ArrowLeft => {
-            rotate_towards = Some(Horizontal::Left);
+            rotate_towards = Some(Horizontal::Left.into());
         }
         Key::Char('s') | Key::ArrowDown => {
-            rotate_towards = Some(Horizontal::Bottom);
+            rotate_towards = Some(Horizontal::Bottom.into());
         }
         Key::Char('d') | Key::ArrowRight => {
-            rotate_towards = Some(Horizontal::Right);
+            rotate_towards = Some(Horizontal::Right.into());
+        }
+        Key::Char('q') => {
+            rotate_towards = Some(Diagonal::TopLeft.into());
+        }
+        Key::Char('e') => {
+            rotate_towards = Some(Diagonal::TopRight.into());
+        }
+        Key::Char('z') => {
+            rotate_towards = Some(Diagonal::BottomLeft.into());
+        }
+        Key::Char('c') => {
+            rotate_towards = Some(Diagonal::BottomRight.into());
         }
         Key::Char('?' | 'h') => {
             write_help_text(term)?;
@@ -46,7 +58,11 @@ pub(super) fn handle_input(
         }
         Key::Escape => return Ok(Some(Action::Quit)),
         _ => {}
-    };
+    }
+
+    // Levels without diagonal tilts ignore the diagonal keys
+    let rotate_towards = rotate_towards
+        .filter(|direction| map_data.win.general.tilt_directions().contains(direction));
 
     if let Some(rotate_towards) = rotate_towards {
         round_stats.moves += 1;
@@ -1,30 +1,235 @@
-use std::{borrow::BorrowMut, ops::Neg, thread::sleep, time::Duration};
+mod reach;
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    thread::sleep,
+    time::Duration,
+};
 
 use console::Term;
 
 use classes::RoundStats;
-use game_classes::{MapData, MapState};
-use maps::{prelude::*, W};
+use game_classes::{DiagonalTilt, GeneralWinConditions, MapData, MapState};
+use maps::prelude::*;
 
 use crate::{
     game::{logic::print_map, setting},
     Result,
 };
 
-struct MovingRock {
-    pos: Pos,
-    direction: Horizontal,
+pub use reach::{stranded_rocks, unreachable_targets};
+
+struct MovingRock<P = Pos, D = Direction> {
+    pos: P,
+    direction: D,
+    /// How many tiles the rock has rolled in this tilt
+    distance: u32,
+    /// The rock came to rest and won't move again in this tilt, e.g. because it rolled onto sand
+    stopped: bool,
+    /// The rock fell into a pit and is no longer on the map
+    fallen: bool,
+    /// The direction in which a clone of the rock leaves the splitter the rock just rolled onto
+    split: Option<D>,
+    color: Option<RockColor>,
+    /// Every position and direction the rock had in this tilt, to stop it when it starts to go in circles
+    visited: Vec<(P, D)>,
+}
+
+/// The tiles which change while the rocks roll.
+#[derive(Clone, Default)]
+struct TileState<P = Pos> {
+    /// Shallow pits which swallowed a rock and are normal floor now
+    filled_pits: HashSet<P>,
+    /// How many hit points the cracked walls lost
+    wall_damage: HashMap<P, u8>,
+    /// Doors which are open right now
+    open_doors: HashSet<P>,
+    /// Switches which were flipped an odd number of times
+    flipped_switches: HashSet<P>,
+    /// The doors which every pressure plate or switch opens and closes
+    links: Vec<(P, Vec<P>)>,
+    /// The partner of every portal
+    portals: Portals,
+}
+
+impl<P: Eq + Hash> TileState<P> {
+    /// Whether a rock can roll onto `object` on `pos`, e.g. because it is an open door or a broken wall.
+    fn is_passable(&self, pos: &P, object: RockKind) -> bool {
+        match object {
+            RockKind::Empty => true,
+            RockKind::CrackedWall(hit_points) => self
+                .wall_damage
+                .get(pos)
+                .is_some_and(|damage| *damage >= hit_points),
+            RockKind::Door { .. } => self.open_doors.contains(pos),
+            _ => false,
+        }
+    }
+}
+
+impl<P, D> MovingRock<P, D> {
+    const fn new(pos: P, direction: D) -> Self {
+        Self {
+            pos,
+            direction,
+            distance: 0,
+            stopped: false,
+            fallen: false,
+            split: None,
+            color: None,
+            visited: Vec::new(),
+        }
+    }
+
+    /// Lets the resting rock roll again towards `direction`.
+    fn restart(&mut self, direction: D) {
+        self.direction = direction;
+        self.distance = 0;
+        self.stopped = false;
+        self.split = None;
+        self.visited.clear();
+    }
+}
+
+/// How the tiles of a grid change the movement of a rock that rolls onto them.
+trait RockMovement: Grid<Item = Tile> {
+    /// Adjusts the movement of `moving_rock`, which rolls onto `next_pos`.
+    /// `next_pos` can change as well, e.g. when the rock goes through a portal.
+    ///
+    /// Returns `false` if the rock can't roll onto the tile.
+    fn adjust_rock_movement(
+        &self,
+        rules: &GeneralWinConditions,
+        moving_rock: &mut MovingRock<Self::Pos, Self::Direction>,
+        next_pos: &mut Self::Pos,
+        tiles: &TileState<Self::Pos>,
+    ) -> bool;
+
+    /// Whether a rock that rolls onto `pos` comes to rest there.
+    fn stops_rock_on(&self, pos: &Self::Pos) -> bool {
+        self.get(pos).is_some_and(|tile| tile.floor == Floor::Sand)
+    }
+
+    /// Whether a rock on `pos` keeps sliding, even when it reached the `max_roll_distance`.
+    fn slides_on(&self, pos: &Self::Pos) -> bool {
+        self.get(pos).is_some_and(|tile| tile.floor == Floor::Ice)
+    }
+
+    /// The direction in which a rock resting on `pos` is pushed after the rocks stopped rolling.
+    fn pushes_rock_towards(&self, pos: &Self::Pos) -> Option<Self::Direction>;
+
+    /// Whether a rock that rolls onto `pos` falls into a pit, which isn't filled yet.
+    fn swallows_rock_on(&self, pos: &Self::Pos, tiles: &TileState<Self::Pos>) -> bool {
+        !tiles.filled_pits.contains(pos)
+            && self
+                .get(pos)
+                .is_some_and(|tile| matches!(tile.floor, Floor::Pit | Floor::ShallowPit))
+    }
 }
 
-impl MovingRock {
-    fn try_get_next_pos(&self) -> Option<Pos> {
-        W(&self.pos).try_add(&self.direction.to_offset())
+impl RockMovement for WrappedMap<'_> {
+    fn adjust_rock_movement(
+        &self,
+        rules: &GeneralWinConditions,
+        moving_rock: &mut MovingRock,
+        next_pos: &mut Pos,
+        tiles: &TileState,
+    ) -> bool {
+        try_adjust_rock_movement_from_new_tile(self, rules, moving_rock, next_pos, tiles)
+    }
+
+    fn pushes_rock_towards(&self, pos: &Pos) -> Option<Direction> {
+        match self.map.get(pos)?.floor {
+            Floor::Conveyor(direction) => Some(direction.into()),
+            _ => None,
+        }
+    }
+}
+
+/// Hex maps can only be rolled with [`roll_rocks`] so far. Levels, [`tilt`] and the input are square only,
+/// so the rocks have to be sorted and the tiles prepared by the caller.
+///
+/// Mirrors, `-` splitters, portals and the left and right gates work like on square maps, see [`Mirror::reflect_hex`].
+/// Single reflectors, `|` splitters and the top and bottom gates have no hex direction to work with and block rocks.
+impl RockMovement for HexMap {
+    fn adjust_rock_movement(
+        &self,
+        _rules: &GeneralWinConditions,
+        moving_rock: &mut MovingRock<Axial, HexDirection>,
+        next_pos: &mut Axial,
+        tiles: &TileState<Axial>,
+    ) -> bool {
+        let mut entered_portals = Vec::new();
+
+        loop {
+            let Some(tile) = self.get(next_pos) else {
+                return false;
+            };
+
+            match tile.object {
+                RockKind::Empty => {}
+                RockKind::CrackedWall(_) | RockKind::Door { .. } => {
+                    if !tiles.is_passable(next_pos, tile.object) {
+                        return false;
+                    }
+                }
+                RockKind::Mirror(mirror) => {
+                    moving_rock.direction = mirror.reflect_hex(moving_rock.direction);
+                }
+                RockKind::Splitter(Splitter::LeftRight) => {
+                    if let Some((direction, clone_direction)) =
+                        Splitter::LeftRight.split_hex(moving_rock.direction)
+                    {
+                        moving_rock.direction = direction;
+                        moving_rock.split = Some(clone_direction);
+                    }
+                }
+                RockKind::Gate(direction) => {
+                    if HexDirection::from_horizontal(direction) != Some(moving_rock.direction) {
+                        return false;
+                    }
+                }
+                RockKind::Portal(_) => {
+                    if entered_portals.contains(next_pos) {
+                        return false;
+                    }
+                    entered_portals.push(*next_pos);
+
+                    let Some(exit_pos) = axial_to_offset(*next_pos)
+                        .and_then(|pos| tiles.portals.partner(&pos))
+                        .and_then(|partner| {
+                            self.neighbor(&offset_to_axial(partner), moving_rock.direction)
+                        })
+                    else {
+                        return false;
+                    };
+
+                    *next_pos = exit_pos;
+                    continue;
+                }
+                RockKind::RoundRock
+                | RockKind::ColoredRock(_)
+                | RockKind::SquareRock
+                | RockKind::SingleReflect(_)
+                | RockKind::Splitter(Splitter::TopBottom) => return false,
+            }
+
+            return true;
+        }
+    }
+
+    fn pushes_rock_towards(&self, pos: &Axial) -> Option<HexDirection> {
+        match self.get(pos)?.floor {
+            Floor::Conveyor(direction) => HexDirection::from_horizontal(direction),
+            _ => None,
+        }
     }
 }
 
 pub(super) fn tilt(
     term: &Term,
-    rotate_towards: Horizontal,
+    rotate_towards: Direction,
     map_data: &MapData,
     state: &mut MapState,
     round_stats: &RoundStats,
@@ -33,8 +238,8 @@ pub(super) fn tilt(
         .rock_positions
         .iter()
         .map(|pos| MovingRock {
-            pos: *pos,
-            direction: rotate_towards,
+            color: state.rock_colors.get(pos).copied(),
+            ..MovingRock::new(*pos, rotate_towards)
         })
         .collect::<Vec<_>>();
 
@@ -46,102 +251,641 @@ pub(super) fn tilt(
         .move_delay()
         .unwrap_or_else(|| Duration::from_millis(150));
 
+    let mut tiles = TileState {
+        filled_pits: state.filled_pits.clone(),
+        wall_damage: state.wall_damage.clone(),
+        open_doors: state.open_doors.clone(),
+        flipped_switches: state.flipped_switches.clone(),
+        links: map_data
+            .links
+            .iter()
+            .map(|link| (link.trigger, link.doors.clone()))
+            .collect(),
+        portals: map_data.portals().clone(),
+    };
+
+    let mut on_step = |moving_rocks: &[MovingRock], tiles: &TileState| {
+        state.rock_positions = collect_rock_positions(moving_rocks);
+        state.rock_colors = moving_rocks
+            .iter()
+            .filter(|rock| !rock.fallen)
+            .filter_map(|rock| rock.color.map(|color| (rock.pos, color)))
+            .collect();
+        state.filled_pits.clone_from(&tiles.filled_pits);
+        state.wall_damage.clone_from(&tiles.wall_damage);
+        state.open_doors.clone_from(&tiles.open_doors);
+        state.flipped_switches.clone_from(&tiles.flipped_switches);
+
+        print_map(term, map_data, state, round_stats)?;
+        sleep(dur);
+
+        Ok(())
+    };
+
+    let grid = WrappedMap {
+        map: &map_data.map,
+        wrap: map_data.win.general.wrap,
+    };
+
+    roll_rocks(
+        &grid,
+        &map_data.win.general,
+        &mut moving_rocks,
+        &mut tiles,
+        &mut on_step,
+    )?;
+    settle_rocks(
+        &grid,
+        &map_data.win.general,
+        rotate_towards,
+        &mut moving_rocks,
+        &mut tiles,
+        &mut on_step,
+    )?;
+
+    state.lost_rocks += moving_rocks
+        .iter()
+        .filter(|moving_rock| moving_rock.fallen)
+        .map(|_| 1)
+        .sum::<u32>();
+
+    Ok(())
+}
+
+/// Moves the rocks step by step until none of them can move anymore.
+/// Rocks which fall into pits are removed, and the changes of the tiles are tracked in `tiles`.
+/// Every rock which rolled and stopped against a cracked wall damages it.
+/// The doors are opened and closed after every step, so rocks can roll through doors they opened in the same tilt.
+/// `on_step` is called with the moved rocks and tiles after every step.
+fn roll_rocks<G: RockMovement>(
+    grid: &G,
+    rules: &GeneralWinConditions,
+    moving_rocks: &mut Vec<MovingRock<G::Pos, G::Direction>>,
+    tiles: &mut TileState<G::Pos>,
+    mut on_step: impl FnMut(&[MovingRock<G::Pos, G::Direction>], &TileState<G::Pos>) -> Result<()>,
+) -> Result<()> {
+    let mut rock_positions = collect_rock_positions(moving_rocks);
+
     loop {
         let mut any_rock_moved = false;
+        let mut resting_rocks = HashSet::new();
 
-        for current_rock in &mut moving_rocks {
-            any_rock_moved |= try_move_and_update_rock(current_rock, map_data, state);
+        for current_rock in &mut *moving_rocks {
+            if try_move_and_update_rock(current_rock, grid, rules, &rock_positions, tiles) {
+                any_rock_moved = true;
+            } else if !current_rock.fallen {
+                resting_rocks.insert(current_rock.pos);
+            }
         }
 
-        if !any_rock_moved {
+        spawn_split_clones(grid, rules, moving_rocks, tiles);
+
+        rock_positions = collect_rock_positions(moving_rocks);
+        let any_door_moved = update_doors(grid, &rock_positions, &resting_rocks, tiles);
+
+        if !any_rock_moved && !any_door_moved {
+            if damage_cracked_walls(grid, moving_rocks, tiles) {
+                on_step(moving_rocks, tiles)?;
+            }
+
             break;
         }
 
-        state.rock_positions = moving_rocks.iter().map(|rock| rock.pos).collect();
+        on_step(moving_rocks, tiles)?;
+    }
 
-        print_map(term, map_data, state, round_stats)?;
-        sleep(dur);
+    Ok(())
+}
+
+/// The phase after the rocks stopped rolling, in which the tiles act on the resting rocks.
+/// Every rock on a conveyor is pushed one tile, after which the rocks roll towards `rotate_towards` again.
+/// Rocks which rolled onto sand during the tilt stay there.
+///
+/// This repeats until nothing changes anymore, or the rocks come to rest where they already were before,
+/// e.g. because of conveyors pointing at each other.
+///
+/// This isn't a separate step after [`tilt`], because it needs the state of the rocks which just rolled,
+/// like which of them were stopped by sand. New effects of resting rocks belong into [`apply_tile_effects`].
+fn settle_rocks<G: RockMovement>(
+    grid: &G,
+    rules: &GeneralWinConditions,
+    rotate_towards: G::Direction,
+    moving_rocks: &mut Vec<MovingRock<G::Pos, G::Direction>>,
+    tiles: &mut TileState<G::Pos>,
+    mut on_step: impl FnMut(&[MovingRock<G::Pos, G::Direction>], &TileState<G::Pos>) -> Result<()>,
+) -> Result<()> {
+    let mut settled_positions = Vec::new();
+
+    loop {
+        let rock_positions = collect_rock_positions(moving_rocks);
+        if settled_positions.contains(&rock_positions) {
+            break;
+        }
+        settled_positions.push(rock_positions);
+
+        if !apply_tile_effects(grid, rules, moving_rocks, tiles) {
+            break;
+        }
+
+        on_step(moving_rocks, tiles)?;
+
+        for moving_rock in moving_rocks.iter_mut().filter(|rock| !rock.fallen) {
+            let on_sand = moving_rock.stopped && grid.stops_rock_on(&moving_rock.pos);
+
+            moving_rock.restart(rotate_towards);
+            moving_rock.stopped = on_sand;
+        }
+
+        roll_rocks(grid, rules, moving_rocks, tiles, &mut on_step)?;
     }
 
     Ok(())
 }
 
-fn try_move_and_update_rock(
-    moving_rock: &mut MovingRock,
-    map_data: &MapData,
-    state: &MapState,
+/// Pushes every rock that rests on a conveyor one tile in the conveyor's direction.
+///
+/// Returns whether any rock moved.
+fn apply_tile_effects<G: RockMovement>(
+    grid: &G,
+    rules: &GeneralWinConditions,
+    moving_rocks: &mut Vec<MovingRock<G::Pos, G::Direction>>,
+    tiles: &mut TileState<G::Pos>,
 ) -> bool {
-    let Some(next_pos) = moving_rock.try_get_next_pos() else {
+    let mut rock_positions = collect_rock_positions(moving_rocks);
+    let mut any_rock_moved = false;
+
+    for moving_rock in moving_rocks.iter_mut().filter(|rock| !rock.fallen) {
+        let Some(direction) = grid.pushes_rock_towards(&moving_rock.pos) else {
+            continue;
+        };
+
+        let old_pos = moving_rock.pos;
+        moving_rock.restart(direction);
+
+        if try_move_and_update_rock(moving_rock, grid, rules, &rock_positions, tiles) {
+            // A rock pushed onto ice can't stop there and keeps sliding in the same direction
+            while !moving_rock.fallen
+                && grid.slides_on(&moving_rock.pos)
+                && try_move_and_update_rock(moving_rock, grid, rules, &rock_positions, tiles)
+            {
+            }
+
+            rock_positions.remove(&old_pos);
+            if !moving_rock.fallen {
+                rock_positions.insert(moving_rock.pos);
+            }
+
+            any_rock_moved = true;
+        }
+    }
+
+    spawn_split_clones(grid, rules, moving_rocks, tiles);
+
+    any_rock_moved
+}
+
+/// Lets a clone of every rock, which just rolled onto a splitter, roll out of the other side of the splitter.
+/// Without `clone_split_rocks` or if the other side is blocked, no clone is created.
+fn spawn_split_clones<G: RockMovement>(
+    grid: &G,
+    rules: &GeneralWinConditions,
+    moving_rocks: &mut Vec<MovingRock<G::Pos, G::Direction>>,
+    tiles: &mut TileState<G::Pos>,
+) {
+    let clones = moving_rocks
+        .iter_mut()
+        .filter_map(|rock| {
+            rock.split.take().map(|direction| MovingRock {
+                color: rock.color,
+                ..MovingRock::new(rock.pos, direction)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if !rules.clone_split_rocks {
+        return;
+    }
+
+    let mut rock_positions = collect_rock_positions(moving_rocks);
+
+    for mut clone in clones {
+        if try_move_and_update_rock(&mut clone, grid, rules, &rock_positions, tiles) {
+            if !clone.fallen {
+                rock_positions.insert(clone.pos);
+            }
+
+            moving_rocks.push(clone);
+        }
+    }
+}
+
+/// Opens and closes the linked doors. Every pressure plate with a resting rock on it and every flipped switch
+/// flips the state of its doors. Doors can't close while a rock is in them.
+///
+/// Returns whether any door was opened or closed.
+fn update_doors<G: RockMovement>(
+    grid: &G,
+    rock_positions: &HashSet<G::Pos>,
+    resting_rocks: &HashSet<G::Pos>,
+    tiles: &mut TileState<G::Pos>,
+) -> bool {
+    let mut flipped_doors = HashSet::new();
+
+    for (trigger, doors) in &tiles.links {
+        let active = match grid.get(trigger).map(|tile| tile.floor) {
+            Some(Floor::PressurePlate) => resting_rocks.contains(trigger),
+            Some(Floor::Switch) => tiles.flipped_switches.contains(trigger),
+            _ => false,
+        };
+
+        if active {
+            for door in doors {
+                if !flipped_doors.remove(door) {
+                    flipped_doors.insert(*door);
+                }
+            }
+        }
+    }
+
+    let mut any_door_moved = false;
+
+    for door in tiles.links.iter().flat_map(|(_, doors)| doors) {
+        let initially_open = grid
+            .get(door)
+            .is_some_and(|tile| tile.object == RockKind::Door { open: true });
+
+        if initially_open != flipped_doors.contains(door) {
+            any_door_moved |= tiles.open_doors.insert(*door);
+        } else if !rock_positions.contains(door) {
+            any_door_moved |= tiles.open_doors.remove(door);
+        }
+    }
+
+    any_door_moved
+}
+
+/// Every rock which rolled and stopped against a cracked wall takes one hit point from it.
+///
+/// Returns whether any wall was damaged.
+fn damage_cracked_walls<G: RockMovement>(
+    grid: &G,
+    moving_rocks: &[MovingRock<G::Pos, G::Direction>],
+    tiles: &mut TileState<G::Pos>,
+) -> bool {
+    let mut any_wall_damaged = false;
+
+    for moving_rock in moving_rocks {
+        if moving_rock.distance == 0 || moving_rock.stopped {
+            continue;
+        }
+
+        let Some(wall_pos) = grid.neighbor(&moving_rock.pos, moving_rock.direction) else {
+            continue;
+        };
+
+        let Some(RockKind::CrackedWall(hit_points)) = grid.get(&wall_pos).map(|tile| tile.object)
+        else {
+            continue;
+        };
+
+        let damage = tiles.wall_damage.entry(wall_pos).or_default();
+        if *damage < hit_points {
+            *damage += 1;
+            any_wall_damaged = true;
+        }
+    }
+
+    any_wall_damaged
+}
+
+fn collect_rock_positions<P: Copy + Eq + Hash, D>(moving_rocks: &[MovingRock<P, D>]) -> HashSet<P> {
+    moving_rocks
+        .iter()
+        .filter(|rock| !rock.fallen)
+        .map(|rock| rock.pos)
+        .collect()
+}
+
+fn try_move_and_update_rock<G: RockMovement>(
+    moving_rock: &mut MovingRock<G::Pos, G::Direction>,
+    grid: &G,
+    rules: &GeneralWinConditions,
+    rock_positions: &HashSet<G::Pos>,
+    tiles: &mut TileState<G::Pos>,
+) -> bool {
+    if moving_rock.stopped {
         return false;
-    };
+    }
+
+    if moving_rock.visited.is_empty() {
+        moving_rock
+            .visited
+            .push((moving_rock.pos, moving_rock.direction));
+    }
 
-    let Some(tile_at_next_position) = map_data.map.get(&next_pos) else {
+    if let Some(max_roll_distance) = rules.max_roll_distance {
+        if moving_rock.distance >= max_roll_distance && !grid.slides_on(&moving_rock.pos) {
+            return false;
+        }
+    }
+
+    let Some(mut next_pos) = grid.neighbor(&moving_rock.pos, moving_rock.direction) else {
         return false;
     };
 
-    if !try_adjust_rock_movement_from_new_tile(tile_at_next_position, moving_rock) {
+    let direction = moving_rock.direction;
+
+    let can_move = grid.adjust_rock_movement(rules, moving_rock, &mut next_pos, tiles)
+        // If the other rock is still moving, this `moving_rock` will wait a turn for it to move out of the way.
+        // This produces a "lagging" motion for this Rock.
+        // When the other Rock doesn't move, this one also wont and the turn will end.
+        && !rock_positions.contains(&next_pos);
+
+    if !can_move {
+        // A reflecting tile only turns the rock once it rolled onto it
+        moving_rock.direction = direction;
+        moving_rock.split = None;
+
         return false;
     }
 
-    // If the other rock is still moving, this `moving_rock` will wait a turn for it to move out of the way.
-    // This produces a "lagging" motion for this Rock.
-    // When the other Rock doesn't move, this one also wont and the turn will end.
-    if state.rock_positions.contains(&next_pos) {
-        return false;
+    moving_rock.pos = next_pos;
+    // Rocks can't stop on ice, so sliding over it doesn't count towards the `max_roll_distance`
+    if !grid.slides_on(&next_pos) {
+        moving_rock.distance += 1;
+    }
+    moving_rock.stopped = grid.stops_rock_on(&next_pos);
+
+    // A rock which gets back to where it already was, e.g. by wrapping around the map, would roll forever
+    if moving_rock
+        .visited
+        .contains(&(next_pos, moving_rock.direction))
+    {
+        moving_rock.stopped = true;
+    } else {
+        moving_rock.visited.push((next_pos, moving_rock.direction));
+    }
+
+    if grid
+        .get(&next_pos)
+        .is_some_and(|tile| tile.floor == Floor::Switch)
+        && !tiles.flipped_switches.remove(&next_pos)
+    {
+        tiles.flipped_switches.insert(next_pos);
     }
 
-    W(moving_rock.pos.borrow_mut()).apply(&next_pos);
+    if grid.swallows_rock_on(&next_pos, tiles) {
+        moving_rock.fallen = true;
+        moving_rock.stopped = true;
+
+        if grid
+            .get(&next_pos)
+            .is_some_and(|tile| tile.floor == Floor::ShallowPit)
+        {
+            tiles.filled_pits.insert(next_pos);
+        }
+    }
 
     true
 }
 
 fn try_adjust_rock_movement_from_new_tile(
-    tile_at_next_position: &Tile,
+    grid: &WrappedMap,
+    rules: &GeneralWinConditions,
     moving_rock: &mut MovingRock,
+    next_pos: &mut Pos,
+    tiles: &TileState,
 ) -> bool {
-    match tile_at_next_position.rock {
-        RockKind::Empty => {}
-        RockKind::RoundRock | RockKind::SquareRock => return false,
-        RockKind::SingleReflect(diagonal) => {
-            let mut reflect_directions = diagonal.horizontals().to_vec();
+    let mut entered_portals = Vec::new();
+    let mut from = moving_rock.pos;
+
+    loop {
+        let Some(tile_at_next_position) = grid.map.get(next_pos) else {
+            return false;
+        };
 
-            reflect_directions.retain(|reflect_dir| {
-                reflect_dir.to_offset() != moving_rock.direction.to_offset().neg()
-            });
+        if !passes_corner(grid, rules, from, moving_rock.direction, tiles) {
+            return false;
+        }
+
+        match tile_at_next_position.object {
+            RockKind::Empty => {}
+            RockKind::RoundRock | RockKind::ColoredRock(_) | RockKind::SquareRock => return false,
+            RockKind::CrackedWall(_) | RockKind::Door { .. } => {
+                if !tiles.is_passable(next_pos, tile_at_next_position.object) {
+                    return false;
+                }
+            }
+            RockKind::Mirror(mirror) => {
+                moving_rock.direction = match moving_rock.direction {
+                    Direction::Straight(direction) => mirror.reflect(direction).into(),
+                    Direction::Diagonal(direction) => mirror.reflect_diagonal(direction).into(),
+                };
+            }
+            RockKind::Splitter(splitter) => match moving_rock.direction {
+                Direction::Straight(direction) => {
+                    if let Some((direction, clone_direction)) = splitter.split(direction) {
+                        moving_rock.direction = direction.into();
+                        moving_rock.split = Some(clone_direction.into());
+                    }
+                }
+                Direction::Diagonal(direction) => {
+                    moving_rock.direction = splitter.deflect_diagonal(direction).into();
+                }
+            },
+            RockKind::Gate(direction) => {
+                if Direction::from(direction) != moving_rock.direction {
+                    return false;
+                }
+            }
+            RockKind::SingleReflect(diagonal) => {
+                match reflect_on_single_reflector(diagonal, moving_rock.direction) {
+                    Some(direction) => moving_rock.direction = direction,
+                    // On ice the rock slides past the closed side of the reflector
+                    None if tile_at_next_position.floor == Floor::Ice => {}
+                    None => return false,
+                }
+            }
+            RockKind::Portal(_) => {
+                // Portals which lead into each other would teleport the rock forever
+                if entered_portals.contains(next_pos) {
+                    return false;
+                }
+                entered_portals.push(*next_pos);
+
+                // The rock comes out on the tile after the partner portal.
+                // If it can't go there, it stops in front of the entry portal.
+                let Some((partner, exit_pos)) =
+                    tiles.portals.partner(next_pos).and_then(|partner| {
+                        Some((partner, grid.neighbor(&partner, moving_rock.direction)?))
+                    })
+                else {
+                    return false;
+                };
 
-            match reflect_directions.len() {
-                1 => moving_rock.direction = reflect_directions[0],
-                _ => return false,
+                from = partner;
+                *next_pos = exit_pos;
+                continue;
             }
         }
+
+        return true;
+    }
+}
+
+/// The direction in which a rock rolling in `direction` leaves a [`RockKind::SingleReflect`] with the open sides `open`.
+///
+/// A straight rock which rolls into an open side leaves through the other open side.
+/// A diagonal rock which rolls into the open corner bounces back, and a diagonal rock which rolls along
+/// the reflecting side passes the reflector.
+///
+/// Returns `None` if the rock rolls against a closed side.
+fn reflect_on_single_reflector(open: Diagonal, direction: Direction) -> Option<Direction> {
+    match direction {
+        Direction::Straight(direction) => {
+            let mut reflect_directions = open.horizontals().to_vec();
+            reflect_directions.retain(|reflect_dir| *reflect_dir != direction.opposite());
+
+            (reflect_directions.len() == 1).then(|| reflect_directions[0].into())
+        }
+        Direction::Diagonal(direction) if direction == open.opposite() => Some(open.into()),
+        Direction::Diagonal(direction) if direction == open => None,
+        Direction::Diagonal(_) => Some(direction),
+    }
+}
+
+/// Whether a rock rolling from `from` in `direction` gets past the tiles beside its path, see [`DiagonalTilt`].
+/// Straight rocks have nothing beside their path.
+fn passes_corner(
+    grid: &WrappedMap,
+    rules: &GeneralWinConditions,
+    from: Pos,
+    direction: Direction,
+    tiles: &TileState,
+) -> bool {
+    let Direction::Diagonal(diagonal) = direction else {
+        return true;
     };
 
-    true
+    if rules.diagonal_tilt == DiagonalTilt::Squeeze {
+        return true;
+    }
+
+    let blocks = |side: Horizontal| {
+        grid.neighbor(&from, side.into())
+            .and_then(|pos| Some((pos, grid.map.get(&pos)?)))
+            .is_none_or(|(pos, tile)| !tiles.is_passable(&pos, tile.object))
+    };
+
+    !diagonal.horizontals().into_iter().all(blocks)
 }
 
-fn sort_rock_for_rotation_fn(rotate_towards: Horizontal, map: &Map) -> Box<dyn Fn(&Pos) -> u32> {
+fn sort_rock_for_rotation_fn(rotate_towards: Direction, map: &Map) -> Box<dyn Fn(&Pos) -> u32> {
     let width = map.width();
     let height = map.height();
 
     match rotate_towards {
-        Horizontal::Top => Box::new(move |pos| pos.y * width + pos.x),
-        Horizontal::Left => Box::new(move |pos| pos.x * height + pos.y),
-        Horizontal::Right => Box::new(move |pos| (width - pos.x) * height + pos.y),
-        Horizontal::Bottom => Box::new(move |pos| (width - pos.y) * width + pos.x),
+        Direction::Straight(Horizontal::Top) => Box::new(move |pos| pos.y * width + pos.x),
+        Direction::Straight(Horizontal::Left) => Box::new(move |pos| pos.x * height + pos.y),
+        Direction::Straight(Horizontal::Right) => {
+            Box::new(move |pos| (width - pos.x) * height + pos.y)
+        }
+        Direction::Straight(Horizontal::Bottom) => {
+            Box::new(move |pos| (height - 1 - pos.y) * width + pos.x)
+        }
+        Direction::Diagonal(Diagonal::TopLeft) => Box::new(move |pos| pos.x + pos.y),
+        Direction::Diagonal(Diagonal::TopRight) => Box::new(move |pos| (width - pos.x) + pos.y),
+        Direction::Diagonal(Diagonal::BottomLeft) => Box::new(move |pos| pos.x + (height - pos.y)),
+        Direction::Diagonal(Diagonal::BottomRight) => {
+            Box::new(move |pos| (width - pos.x) + (height - pos.y))
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use game_classes::{GeneralWinConditions, RockWinConditions, WinCondition};
+    use std::str::FromStr;
+
+    use game_classes::{GeneralWinConditions, Link, RockWinConditions, WinCondition};
 
     use crate::assets::prepare_map;
 
     use super::*;
 
+    fn map_data_from_str(map: &str) -> MapData {
+        MapData::new(
+            Map::from_str(map).expect("Map should parse"),
+            WinCondition {
+                general: GeneralWinConditions::default(),
+                rocks: RockWinConditions::Pos(vec![]),
+            },
+            Vec::new(),
+        )
+    }
+
+    /// Tilts `map` in all `directions` and checks that the rocks end up like in `expected`.
+    fn assert_tilts_to(map: &str, directions: &[impl Into<Direction> + Copy], expected: &str) {
+        assert_tilts_to_with_rules(GeneralWinConditions::default(), map, directions, expected);
+    }
+
+    fn assert_tilts_to_with_rules(
+        rules: GeneralWinConditions,
+        map: &str,
+        directions: &[impl Into<Direction> + Copy],
+        expected: &str,
+    ) {
+        let mut map_data = map_data_from_str(map);
+        map_data.win.general = rules;
+        let mut state = prepare_map(&mut map_data);
+
+        for direction in directions {
+            tilt(
+                &Term::buffered_stdout(),
+                (*direction).into(),
+                &map_data,
+                &mut state,
+                &RoundStats::default(),
+            )
+            .expect("Tilting should not fail");
+        }
+
+        let mut expected = map_data_from_str(expected);
+        let expected_state = prepare_map(&mut expected);
+
+        assert_same_map(&expected.map, &map_data.map);
+        assert_same_state(&expected_state, &state);
+    }
+
+    /// Like `assert_eq!`, but only shows the cells which differ.
+    fn assert_same_map(expected: &Map, actual: &Map) {
+        let diff = expected.diff(actual);
+
+        assert!(
+            diff.is_empty(),
+            "Maps differ, {} -> {}:\n{}",
+            diff.old_size,
+            diff.new_size,
+            diff.changes
+                .iter()
+                .map(|change| format!("{change:?}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    /// Like `assert_eq!`, but shows where the rocks ended up instead of their `expected` positions.
+    fn assert_same_state(expected: &MapState, actual: &MapState) {
+        assert!(
+            expected.diff(actual).is_empty(),
+            "Rocks ended up elsewhere (expected -> actual):\n{}",
+            expected.diff(actual)
+        );
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn spin() {
-        let map = Map::from(
+        let map = Map::from_str(
             r"o . . . . # . . . .
             o . o o # . . . . #
             . . . . . # # . . .
@@ -152,16 +896,14 @@ mod test {
             . . . . . . . o . .
             # . . . . # # # . .
             # o o . . # . . . .",
-        );
+        )
+        .expect("Map should parse");
 
         let win = WinCondition {
-            general: GeneralWinConditions { max_moves: None },
+            general: GeneralWinConditions::default(),
             rocks: RockWinConditions::Pos(vec![]),
         };
-        let mut map_data = MapData {
-            map,
-            win: win.clone(),
-        };
+        let mut map_data = MapData::new(map, win.clone(), Vec::new());
 
         let mut state = prepare_map(&mut map_data);
 
@@ -174,7 +916,7 @@ mod test {
             ] {
                 tilt(
                     &Term::buffered_stdout(),
-                    direction,
+                    direction.into(),
                     &map_data,
                     &mut state,
                     &RoundStats::default(),
@@ -183,8 +925,8 @@ mod test {
             }
         }
 
-        let mut expected = MapData {
-            map: Map::from(
+        let mut expected = MapData::new(
+            Map::from_str(
                 r". . . . . # . . . . 
             . . . . # . . . o # 
             . . . . . # # . . . 
@@ -195,21 +937,940 @@ mod test {
             . . . . . . . o o o 
             # . . . o # # # . o 
             # . o o o # . . . o",
-            ),
+            )
+            .expect("Map should parse"),
             win,
-        };
+            Vec::new(),
+        );
 
         let expected_state = prepare_map(&mut expected);
 
-        let _ = print_map(&Term::stdout(), &map_data, &state, &RoundStats { moves: 1 });
-        let _ = print_map(
-            &Term::stdout(),
-            &expected,
-            &expected_state,
-            &RoundStats { moves: 1 },
+        assert_same_map(&expected.map, &map_data.map);
+        assert_same_state(&expected_state, &state);
+    }
+
+    /// Checks that tilting `map` in all `directions` ends up like tilting the rotated map in the rotated directions.
+    fn assert_rotation_agnostic(map: &str, directions: &[impl Into<Direction> + Copy]) {
+        let map = Map::from_str(map).expect("Map should parse");
+
+        let win = WinCondition {
+            general: GeneralWinConditions::default(),
+            rocks: RockWinConditions::Pos(vec![]),
+        };
+        let mut map_data = MapData::new(map, win, Vec::new());
+        let mut state = prepare_map(&mut map_data);
+        let (width, height) = (map_data.map.width(), map_data.map.height());
+
+        let rotated_map_data = MapData::new(
+            map_data.map.clone(),
+            map_data.win.clone(),
+            map_data.links.clone(),
+        )
+        .transform(Transform::RotateCw);
+        let mut rotated_state = state.clone().transform(Transform::RotateCw, width, height);
+
+        for direction in directions {
+            let direction = (*direction).into();
+
+            tilt(
+                &Term::buffered_stdout(),
+                direction,
+                &map_data,
+                &mut state,
+                &RoundStats::default(),
+            )
+            .expect("Tilting should not fail");
+
+            tilt(
+                &Term::buffered_stdout(),
+                direction.transformed(Transform::RotateCw),
+                &rotated_map_data,
+                &mut rotated_state,
+                &RoundStats::default(),
+            )
+            .expect("Tilting should not fail");
+        }
+
+        assert_eq!(
+            state.transform(Transform::RotateCw, width, height),
+            rotated_state
+        );
+    }
+
+    #[test]
+    fn tilting_a_rotated_map() {
+        assert_rotation_agnostic(
+            r"o . ◢ . #
+            . o . o .
+            # . ◤ . o
+            o . . # .",
+            &[Horizontal::Top, Horizontal::Left, Horizontal::Bottom],
         );
+    }
+
+    #[test]
+    fn tilt_a_tall_map_to_the_bottom() {
+        assert_tilts_to(
+            "\
+            o .
+            . o
+            . .
+            o .
+            . .",
+            &[Horizontal::Bottom],
+            "\
+            . .
+            . .
+            . .
+            o .
+            o o",
+        );
+    }
+
+    #[test]
+    fn void_stops_rocks() {
+        assert_tilts_to(
+            "\
+            o . _ .
+            . . . o
+            _ o _ _",
+            &[Horizontal::Right, Horizontal::Bottom],
+            "\
+            . . _ .
+            . o . o
+            _ o _ _",
+        );
+    }
+
+    #[test]
+    fn tilt_hex_map() {
+        let mut map = HexMap::<Tile>::from_str(
+            "\
+            o . . #
+             . o . .
+            . . o .",
+        )
+        .expect("Map should parse");
+
+        let rock_positions = map
+            .all_axial()
+            .filter(|axial| {
+                map.get(axial)
+                    .is_some_and(|tile| tile.object == RockKind::RoundRock)
+            })
+            .collect::<Vec<_>>();
+        for axial in &rock_positions {
+            if let Some(tile) = map.get_mut(axial) {
+                tile.object = RockKind::Empty;
+            }
+        }
 
-        assert_eq!(expected.map, map_data.map);
-        assert_eq!(expected_state, state);
+        let mut moving_rocks = rock_positions
+            .iter()
+            .map(|axial| MovingRock::new(*axial, HexDirection::TopRight))
+            .collect::<Vec<_>>();
+
+        let mut steps = 0;
+        roll_rocks(
+            &map,
+            &GeneralWinConditions::default(),
+            &mut moving_rocks,
+            &mut TileState::default(),
+            |_, _| {
+                steps += 1;
+                Ok(())
+            },
+        )
+        .expect("Rolling should not fail");
+
+        let mut result = moving_rocks
+            .iter()
+            .map(|rock| axial_to_offset(rock.pos))
+            .collect::<Vec<_>>();
+        result.sort_by_key(|pos| pos.map(|pos| (pos.y, pos.x)));
+
+        assert_eq!(
+            result,
+            vec![
+                Some(Pos::new(0, 0)),
+                Some(Pos::new(2, 0)),
+                Some(Pos::new(2, 1)),
+            ]
+        );
+        assert_eq!(steps, 1);
+    }
+
+    /// Rolls the rocks of the hex `map` towards `direction` and returns the map with the rocks where they stopped.
+    fn roll_on_hex_map(map: &str, direction: HexDirection) -> String {
+        let mut map = HexMap::<Tile>::from_str(map).expect("Map should parse");
+
+        let rock_positions = map
+            .all_axial()
+            .filter(|axial| {
+                map.get(axial)
+                    .is_some_and(|tile| tile.object == RockKind::RoundRock)
+            })
+            .collect::<Vec<_>>();
+        for axial in &rock_positions {
+            if let Some(tile) = map.get_mut(axial) {
+                tile.object = RockKind::Empty;
+            }
+        }
+
+        let mut moving_rocks = rock_positions
+            .iter()
+            .map(|axial| MovingRock::new(*axial, direction))
+            .collect::<Vec<_>>();
+
+        let rules = GeneralWinConditions {
+            clone_split_rocks: true,
+            ..GeneralWinConditions::default()
+        };
+        let mut tiles = TileState {
+            portals: map.offset_map().portals(),
+            ..TileState::default()
+        };
+
+        roll_rocks(&map, &rules, &mut moving_rocks, &mut tiles, |_, _| Ok(()))
+            .expect("Rolling should not fail");
+
+        for rock in &moving_rocks {
+            if let Some(tile) = map.get_mut(&rock.pos) {
+                tile.object = RockKind::RoundRock;
+            }
+        }
+
+        map.offset_map().to_level_string()
+    }
+
+    #[test]
+    fn reflectors_and_portals_on_hex_maps() {
+        assert_eq!(
+            roll_on_hex_map(
+                r". . . .
+                 o . \ .
+                . . . .",
+                HexDirection::Right
+            ),
+            ". . . .\n. . \\ .\n. . o .\n",
+            "the mirror sends the rock to the bottom left"
+        );
+        assert_eq!(
+            roll_on_hex_map(
+                "\
+                . o . . .
+                 . - . . .",
+                HexDirection::BottomRight
+            ),
+            ". . . . .\no - . . o\n",
+            "the splitter sends the rock right and its clone left"
+        );
+        assert_eq!(
+            roll_on_hex_map(
+                "\
+                o . A
+                 A . .",
+                HexDirection::Right
+            ),
+            ". . A\nA . o\n"
+        );
+        assert_eq!(
+            roll_on_hex_map("o . ◢ . | .", HexDirection::Right),
+            ". o ◢ . | .\n",
+            "single reflectors block rocks on hex maps"
+        );
+    }
+
+    #[test]
+    fn portals() {
+        assert_tilts_to("o . A . . A . #", &[Horizontal::Right], ". . A . . A o #");
+        assert_tilts_to(
+            "\
+            o A . .
+            . . A .",
+            &[Horizontal::Right],
+            "\
+            . A . .
+            . . A o",
+        );
+    }
+
+    #[test]
+    fn blocked_portal_exit() {
+        assert_tilts_to("o . A . . A #", &[Horizontal::Right], ". o A . . A #");
+        // Goes through A and B, but the second time through both portals is blocked by the wall
+        assert_tilts_to("o . A B . A B #", &[Horizontal::Right], ". . A B o A B #");
+    }
+
+    #[test]
+    fn sand_stops_rocks() {
+        assert_tilts_to("o . : . .", &[Horizontal::Right], ". . :o . .");
+        assert_tilts_to(
+            "o . : . .",
+            &[Horizontal::Right, Horizontal::Right],
+            ". . : . o",
+        );
+        // The conveyor lets the rocks roll again, but the one on the sand stays there
+        assert_tilts_to(
+            "\
+            o : . . .
+            . . . . ⇑o",
+            &[Horizontal::Right],
+            "\
+            . :o . . o
+            . . . . ⇑",
+        );
+    }
+
+    #[test]
+    fn max_roll_distance() {
+        let rules = GeneralWinConditions {
+            max_roll_distance: Some(2),
+            ..Default::default()
+        };
+
+        assert_tilts_to_with_rules(
+            rules.clone(),
+            "o . . . .",
+            &[Horizontal::Right],
+            ". . o . .",
+        );
+        assert_tilts_to_with_rules(
+            rules.clone(),
+            "o ~ ~ ~ . . .",
+            &[Horizontal::Right],
+            ". ~ ~ ~ . o .",
+        );
+        // Only the two tiles beyond the ice count towards the roll distance
+        assert_tilts_to_with_rules(
+            rules,
+            "o ~ ~ . . . .",
+            &[Horizontal::Right],
+            ". ~ ~ . o . .",
+        );
+    }
+
+    #[test]
+    fn conveyors_push_rocks_over_ice() {
+        assert_tilts_to("⇒o ~ ~ . #", &[Horizontal::Top], "⇒ ~ ~ o #");
+    }
+
+    #[test]
+    fn ice_slides_past_reflectors() {
+        assert_tilts_to("o ~◣ . .", &[Horizontal::Right], ". ~◣ . o");
+        assert_tilts_to(
+            "\
+            o ◣ . .
+            . . . .",
+            &[Horizontal::Right],
+            "\
+            o ◣ . .
+            . . . .",
+        );
+    }
+
+    #[test]
+    fn pits_swallow_rocks() {
+        let mut map_data = map_data_from_str(
+            "\
+            o . x .
+            o o u .",
+        );
+        let mut state = prepare_map(&mut map_data);
+
+        tilt(
+            &Term::buffered_stdout(),
+            Horizontal::Right.into(),
+            &map_data,
+            &mut state,
+            &RoundStats::default(),
+        )
+        .expect("Tilting should not fail");
+
+        assert_eq!(state.rock_positions, HashSet::from([Pos::new(3, 1)]));
+        assert_eq!(state.filled_pits, HashSet::from([Pos::new(2, 1)]));
+        assert_eq!(state.lost_rocks, 2);
+    }
+
+    #[test]
+    fn gates_only_let_rocks_pass_in_their_direction() {
+        assert_tilts_to("o → . .", &[Horizontal::Right], ". → . o");
+        assert_tilts_to(". → . o", &[Horizontal::Left, Horizontal::Right], ". → . o");
+        assert_tilts_to(
+            "\
+            o .
+            ↑ .
+            . .",
+            &[Horizontal::Bottom],
+            "\
+            o .
+            ↑ .
+            . .",
+        );
+    }
+
+    #[test]
+    fn conveyors_push_resting_rocks() {
+        assert_tilts_to(
+            "\
+            o . ⇓
+            . . .",
+            &[Horizontal::Right],
+            "\
+            . . ⇓
+            . . o",
+        );
+    }
+
+    #[test]
+    fn conveyors_pushing_back_and_forth() {
+        // The rock is pushed against the tilt and rolls back onto the conveyor
+        assert_tilts_to("o ⇐", &[Horizontal::Right], ". ⇐o");
+        // The conveyors push the rock back and forth between them
+        assert_tilts_to("⇒o ⇐ #", &[Horizontal::Top], "⇒o ⇐ #");
+    }
+
+    #[test]
+    fn mirrors_deflect_from_all_sides() {
+        assert_tilts_to(
+            "\
+            . . .
+            o . /",
+            &[Horizontal::Right],
+            "\
+            . . o
+            . . /",
+        );
+        assert_tilts_to(
+            "\
+            . . o
+            . . /
+            . . .",
+            &[Horizontal::Bottom],
+            "\
+            . . .
+            o . /
+            . . .",
+        );
+        assert_tilts_to(
+            "\
+            o . \\
+            . . .",
+            &[Horizontal::Right],
+            "\
+            . . \\
+            . . o",
+        );
+        assert_tilts_to(
+            "\
+            . . .
+            . . \\
+            . . o",
+            &[Horizontal::Top],
+            "\
+            . . .
+            o . \\
+            . . .",
+        );
+    }
+
+    #[test]
+    fn splitters() {
+        let map = "\
+            . . .
+            o . |
+            . . .";
+
+        assert_tilts_to(
+            map,
+            &[Horizontal::Right],
+            "\
+            . . .
+            . . |
+            . . o",
+        );
+        assert_tilts_to_with_rules(
+            GeneralWinConditions {
+                clone_split_rocks: true,
+                ..Default::default()
+            },
+            map,
+            &[Horizontal::Right],
+            "\
+            . . o
+            . . |
+            . . o",
+        );
+        // Rocks rolling along the splitter pass it
+        assert_tilts_to("o\n|\n.", &[Horizontal::Bottom], ".\n|\no");
+    }
+
+    #[test]
+    fn spin_with_mirrors_and_splitters() {
+        let map = r"o . / . # o
+            . o . o - .
+            # . \ . o .
+            o | . # . /
+            . o . \ . o";
+        let directions = [
+            Horizontal::Top,
+            Horizontal::Left,
+            Horizontal::Bottom,
+            Horizontal::Right,
+            Horizontal::Top,
+            Horizontal::Left,
+        ];
+
+        assert_rotation_agnostic(map, &directions);
+
+        let mut map_data = map_data_from_str(map);
+        let mut state = prepare_map(&mut map_data);
+
+        for direction in directions {
+            tilt(
+                &Term::buffered_stdout(),
+                direction.into(),
+                &map_data,
+                &mut state,
+                &RoundStats::default(),
+            )
+            .expect("Tilting should not fail");
+        }
+
+        // The rock on (2, 2) rests on the mirror, so the board can't be written as a level string
+        assert_eq!(
+            state.rock_positions,
+            HashSet::from([
+                Pos::new(0, 0),
+                Pos::new(5, 0),
+                Pos::new(0, 1),
+                Pos::new(1, 1),
+                Pos::new(1, 2),
+                Pos::new(2, 2),
+                Pos::new(4, 3),
+                Pos::new(0, 4),
+            ])
+        );
+    }
+
+    #[test]
+    fn cracked_walls_break() {
+        let mut map_data = map_data_from_str("o . %2 .");
+        let mut state = prepare_map(&mut map_data);
+
+        for (direction, damage, rock_x) in [
+            (Horizontal::Right, Some(&1), 1),
+            // A rock which doesn't roll doesn't damage the wall
+            (Horizontal::Right, Some(&1), 1),
+            (Horizontal::Left, Some(&1), 0),
+            (Horizontal::Right, Some(&2), 1),
+        ] {
+            tilt(
+                &Term::buffered_stdout(),
+                direction.into(),
+                &map_data,
+                &mut state,
+                &RoundStats::default(),
+            )
+            .expect("Tilting should not fail");
+
+            assert_eq!(state.wall_damage.get(&Pos::new(2, 0)), damage);
+            assert_eq!(state.rock_positions, HashSet::from([Pos::new(rock_x, 0)]));
+        }
+
+        assert_eq!(
+            state.current_tile(&Pos::new(2, 0), &map_data.map[Pos::new(2, 0)]),
+            Tile::default()
+        );
+
+        tilt(
+            &Term::buffered_stdout(),
+            Horizontal::Right.into(),
+            &map_data,
+            &mut state,
+            &RoundStats::default(),
+        )
+        .expect("Tilting should not fail");
+
+        assert_eq!(state.rock_positions, HashSet::from([Pos::new(3, 0)]));
+    }
+
+    #[test]
+    fn pressure_plates_open_doors_within_a_tilt() {
+        let mut map_data = map_data_from_str(
+            "\
+            o . = #
+            . o & .",
+        );
+        map_data.links = vec![Link {
+            trigger: Pos::new(2, 0),
+            doors: vec![Pos::new(2, 1)],
+        }];
+        let mut state = prepare_map(&mut map_data);
+
+        tilt(
+            &Term::buffered_stdout(),
+            Horizontal::Right.into(),
+            &map_data,
+            &mut state,
+            &RoundStats::default(),
+        )
+        .expect("Tilting should not fail");
+
+        assert_eq!(
+            state.rock_positions,
+            HashSet::from([Pos::new(2, 0), Pos::new(3, 1)])
+        );
+        assert_eq!(state.open_doors, HashSet::from([Pos::new(2, 1)]));
+
+        tilt(
+            &Term::buffered_stdout(),
+            Horizontal::Left.into(),
+            &map_data,
+            &mut state,
+            &RoundStats::default(),
+        )
+        .expect("Tilting should not fail");
+
+        assert_eq!(
+            state.rock_positions,
+            HashSet::from([Pos::new(0, 0), Pos::new(0, 1)])
+        );
+        assert_eq!(state.open_doors, HashSet::new());
+    }
+
+    #[test]
+    fn switches_flip_doors() {
+        let mut map_data = map_data_from_str(
+            "\
+            o * . #
+            . . o &",
+        );
+        map_data.links = vec![Link {
+            trigger: Pos::new(1, 0),
+            doors: vec![Pos::new(3, 1)],
+        }];
+        let mut state = prepare_map(&mut map_data);
+
+        tilt(
+            &Term::buffered_stdout(),
+            Horizontal::Right.into(),
+            &map_data,
+            &mut state,
+            &RoundStats::default(),
+        )
+        .expect("Tilting should not fail");
+
+        assert_eq!(
+            state.rock_positions,
+            HashSet::from([Pos::new(2, 0), Pos::new(3, 1)])
+        );
+        assert_eq!(state.flipped_switches, HashSet::from([Pos::new(1, 0)]));
+        assert_eq!(state.open_doors, HashSet::from([Pos::new(3, 1)]));
+    }
+
+    #[test]
+    fn colored_rocks_keep_their_color() {
+        let mut map_data = map_data_from_str(
+            "\
+            r . o
+            . . |
+            b . .",
+        );
+        map_data.win.general.clone_split_rocks = true;
+        let mut state = prepare_map(&mut map_data);
+
+        for direction in [Horizontal::Bottom, Horizontal::Right] {
+            tilt(
+                &Term::buffered_stdout(),
+                direction.into(),
+                &map_data,
+                &mut state,
+                &RoundStats::default(),
+            )
+            .expect("Tilting should not fail");
+        }
+
+        assert_eq!(
+            state.rock_colors,
+            HashMap::from([
+                (Pos::new(2, 0), RockColor::Red),
+                (Pos::new(2, 1), RockColor::Red),
+                (Pos::new(1, 2), RockColor::Blue),
+            ])
+        );
+        assert!(state.rock_positions.contains(&Pos::new(2, 2)));
+        assert_eq!(state.rock_positions.len(), 4);
+    }
+
+    #[test]
+    fn wrap_around_edges() {
+        let wrap = |wrap| GeneralWinConditions {
+            wrap,
+            ..Default::default()
+        };
+
+        assert_tilts_to_with_rules(
+            wrap(Wrap::Horizontal),
+            ". o # .",
+            &[Horizontal::Left],
+            ". . # o",
+        );
+        assert_tilts_to_with_rules(
+            wrap(Wrap::Vertical),
+            ". o # .",
+            &[Horizontal::Left],
+            "o . # .",
+        );
+        assert_tilts_to_with_rules(
+            wrap(Wrap::Both),
+            "\
+            . . .
+            . # .
+            . o .",
+            &[Horizontal::Bottom],
+            "\
+            . o .
+            . # .
+            . . .",
+        );
+    }
+
+    #[test]
+    fn wrapping_all_the_way_around_stops_where_it_started() {
+        assert_tilts_to_with_rules(
+            GeneralWinConditions {
+                wrap: Wrap::Horizontal,
+                ..Default::default()
+            },
+            ". o . .",
+            &[Horizontal::Right],
+            ". o . .",
+        );
+    }
+
+    fn diagonal(diagonal_tilt: DiagonalTilt) -> GeneralWinConditions {
+        GeneralWinConditions {
+            diagonal_tilt,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diagonal_tilts() {
+        assert_tilts_to_with_rules(
+            diagonal(DiagonalTilt::On),
+            "\
+            o . .
+            . . .
+            . o #",
+            &[Diagonal::BottomRight],
+            "\
+            . . .
+            . o .
+            . o #",
+        );
+        assert_tilts_to_with_rules(
+            diagonal(DiagonalTilt::On),
+            "\
+            . . o
+            . . .
+            o . .",
+            &[Diagonal::BottomLeft, Diagonal::TopLeft],
+            "\
+            o . .
+            . . .
+            o . .",
+        );
+    }
+
+    #[test]
+    fn squeezing_through_corners() {
+        let map = "\
+            o # .
+            # . .
+            . . .";
+
+        assert_tilts_to_with_rules(
+            diagonal(DiagonalTilt::On),
+            map,
+            &[Diagonal::BottomRight],
+            map,
+        );
+        assert_tilts_to_with_rules(
+            diagonal(DiagonalTilt::Squeeze),
+            map,
+            &[Diagonal::BottomRight],
+            "\
+            . # .
+            # . .
+            . . o",
+        );
+        assert_tilts_to_with_rules(
+            diagonal(DiagonalTilt::On),
+            "\
+            o # .
+            . . _
+            . . .",
+            &[Diagonal::BottomRight],
+            "\
+            . # .
+            . . _
+            . . o",
+        );
+        assert_tilts_to_with_rules(
+            diagonal(DiagonalTilt::On),
+            "\
+            o & .
+            & . .",
+            &[Diagonal::BottomRight],
+            "\
+            o & .
+            & . .",
+        );
+    }
+
+    #[test]
+    fn single_reflectors_with_diagonal_rocks() {
+        let rules = diagonal(DiagonalTilt::On);
+
+        // Rolls along the reflecting side
+        assert_tilts_to_with_rules(
+            rules.clone(),
+            "\
+            o . .
+            . ◣ .
+            . . .",
+            &[Diagonal::BottomRight],
+            "\
+            . . .
+            . ◣ .
+            . . o",
+        );
+        // Runs into the closed corner
+        assert_tilts_to_with_rules(
+            rules.clone(),
+            "\
+            . . .
+            . ◣ .
+            o . .",
+            &[Diagonal::TopRight],
+            "\
+            . . .
+            . ◣ .
+            o . .",
+        );
+        assert_tilts_to_with_rules(
+            rules.clone(),
+            "\
+            . . .
+            . ~◣ .
+            o . .",
+            &[Diagonal::TopRight],
+            "\
+            . . o
+            . ~◣ .
+            . . .",
+        );
+        // Bounces back out of the open corner
+        assert_tilts_to_with_rules(
+            rules,
+            "\
+            . . . o
+            . . . .
+            . ◣ . #
+            . . . .",
+            &[Diagonal::BottomLeft],
+            "\
+            . . . o
+            . . . .
+            . ◣ . #
+            . . . .",
+        );
+    }
+
+    #[test]
+    fn mirrors_splitters_and_gates_with_diagonal_rocks() {
+        let rules = diagonal(DiagonalTilt::On);
+
+        assert_tilts_to_with_rules(
+            rules.clone(),
+            "\
+            . . .
+            . / .
+            o . .",
+            &[Diagonal::TopRight],
+            "\
+            . . o
+            . / .
+            . . .",
+        );
+        assert_tilts_to_with_rules(
+            rules.clone(),
+            "\
+            o . . .
+            . . . .
+            . . ╲ .
+            . . . .",
+            &[Diagonal::BottomRight],
+            "\
+            . . . .
+            . . . .
+            . . ╲ .
+            . . . o",
+        );
+        assert_tilts_to_with_rules(
+            rules.clone(),
+            "\
+            o . . .
+            . . . .
+            . . / .
+            . . . .",
+            &[Diagonal::BottomRight],
+            "\
+            o . . .
+            . . . .
+            . . / .
+            . . . .",
+        );
+        assert_tilts_to_with_rules(
+            rules.clone(),
+            "\
+            o . .
+            . | .
+            . . .",
+            &[Diagonal::BottomRight],
+            "\
+            . . .
+            . | .
+            . o .",
+        );
+        assert_tilts_to_with_rules(
+            rules,
+            "\
+            o . .
+            . → .
+            . . .",
+            &[Diagonal::BottomRight],
+            "\
+            o . .
+            . → .
+            . . .",
+        );
+    }
+
+    #[test]
+    fn tilting_a_rotated_map_diagonally() {
+        assert_rotation_agnostic(
+            r"o . ◢ . #
+            . o / o .
+            # . ◤ . o
+            o | . # .",
+            &[
+                Diagonal::BottomRight,
+                Diagonal::TopRight,
+                Diagonal::BottomLeft,
+            ],
+        );
     }
 }
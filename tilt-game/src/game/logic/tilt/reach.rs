@@ -0,0 +1,351 @@
+use std::collections::{HashMap, HashSet};
+
+use game_classes::{GeneralWinConditions, MapData, MapState, RockWinConditions};
+use maps::prelude::*;
+
+use super::{try_move_and_update_rock, MovingRock, RockMovement, TileState};
+
+/// Every cell which the rock on each position could ever reach, under any sequence of tilts.
+///
+/// Other rocks can stop a rock anywhere on its way, so every cell a rock rolls over counts as reached,
+/// and the search goes on from there. Conveyors push the rocks resting on them, and with `clone_split_rocks`
+/// the clones of a splitter roll on from it as well. Doors count as open, cracked walls as broken
+/// and shallow pits as filled, because they could be later on. So this can include too many cells, but never misses one.
+pub fn reachable_cells(map_data: &MapData, state: &MapState) -> HashMap<Pos, HashSet<Pos>> {
+    let mut search = ReachSearch::new(map_data);
+
+    state
+        .rock_positions
+        .iter()
+        .map(|start| (*start, search.reachable_from([*start])))
+        .collect()
+}
+
+/// The targets which no rock can reach, see [`reachable_cells`].
+/// Colored targets only count as reachable for rocks with their color.
+pub fn unreachable_targets(map_data: &MapData, state: &MapState) -> Vec<Pos> {
+    let mut search = ReachSearch::new(map_data);
+
+    match &map_data.win.rocks {
+        RockWinConditions::Pos(targets) | RockWinConditions::Exit(targets) => {
+            let reachable = search.reachable_from(state.rock_positions.iter().copied());
+
+            targets
+                .iter()
+                .filter(|target| !reachable.contains(target))
+                .copied()
+                .collect()
+        }
+        RockWinConditions::Colored(targets) => {
+            let mut reachable_by_color = HashMap::new();
+
+            targets
+                .iter()
+                .filter(|(target, color)| {
+                    !reachable_by_color
+                        .entry(*color)
+                        .or_insert_with(|| {
+                            search.reachable_from(
+                                state
+                                    .rock_positions
+                                    .iter()
+                                    .filter(|pos| state.rock_colors.get(pos) == Some(color))
+                                    .copied(),
+                            )
+                        })
+                        .contains(target)
+                })
+                .map(|(target, _)| *target)
+                .collect()
+        }
+    }
+}
+
+/// The rocks which can't reach any target, see [`reachable_cells`].
+/// A colored rock only counts targets with its color.
+pub fn stranded_rocks(map_data: &MapData, state: &MapState) -> Vec<Pos> {
+    let mut stranded = reachable_cells(map_data, state)
+        .into_iter()
+        .filter(|(start, reachable)| match &map_data.win.rocks {
+            RockWinConditions::Pos(targets) | RockWinConditions::Exit(targets) => {
+                !targets.iter().any(|target| reachable.contains(target))
+            }
+            RockWinConditions::Colored(targets) => !targets.iter().any(|(target, color)| {
+                state.rock_colors.get(start) == Some(color) && reachable.contains(target)
+            }),
+        })
+        .map(|(start, _)| start)
+        .collect::<Vec<_>>();
+
+    stranded.sort_unstable_by_key(|pos| (pos.y, pos.x));
+
+    stranded
+}
+
+/// Rolls a single rock from every cell in every direction, and remembers where it rolled over.
+struct ReachSearch<'a> {
+    grid: WrappedMap<'a>,
+    rules: &'a GeneralWinConditions,
+    tiles: TileState,
+    rolled_over: HashMap<Pos, Vec<Pos>>,
+}
+
+impl<'a> ReachSearch<'a> {
+    fn new(map_data: &'a MapData) -> Self {
+        let mut tiles = TileState {
+            portals: map_data.portals().clone(),
+            ..TileState::default()
+        };
+
+        for (pos, tile) in &map_data.map {
+            match tile.object {
+                RockKind::CrackedWall(hit_points) => {
+                    tiles.wall_damage.insert(pos, hit_points);
+                }
+                RockKind::Door { .. } => {
+                    tiles.open_doors.insert(pos);
+                }
+                _ => {}
+            }
+
+            if tile.floor == Floor::ShallowPit {
+                tiles.filled_pits.insert(pos);
+            }
+        }
+
+        Self {
+            grid: WrappedMap {
+                map: &map_data.map,
+                wrap: map_data.win.general.wrap,
+            },
+            rules: &map_data.win.general,
+            tiles,
+            rolled_over: HashMap::new(),
+        }
+    }
+
+    /// Every cell which a rock starting on any of `starts` could reach.
+    fn reachable_from(&mut self, starts: impl IntoIterator<Item = Pos>) -> HashSet<Pos> {
+        let mut queue = starts.into_iter().collect::<Vec<_>>();
+        let mut reachable = queue.iter().copied().collect::<HashSet<_>>();
+
+        while let Some(pos) = queue.pop() {
+            for next in self.rolled_over(pos) {
+                if reachable.insert(next) {
+                    queue.push(next);
+                }
+            }
+        }
+
+        reachable
+    }
+
+    /// The cells which a rock on `pos` rolls over, when the map is tilted in any direction
+    /// or a conveyor pushes it, including the cells its split clones roll over.
+    fn rolled_over(&mut self, pos: Pos) -> Vec<Pos> {
+        if let Some(cells) = self.rolled_over.get(&pos) {
+            return cells.clone();
+        }
+
+        let mut rolls = self
+            .rules
+            .tilt_directions()
+            .iter()
+            .copied()
+            .chain(self.grid.pushes_rock_towards(&pos))
+            .map(|direction| MovingRock::new(pos, direction))
+            .collect::<Vec<_>>();
+        let mut clones = HashSet::new();
+        let mut cells = Vec::new();
+
+        while let Some(mut moving_rock) = rolls.pop() {
+            // Every roll starts on the same tiles, so a flipped switch of one roll doesn't affect the others
+            let mut tiles = self.tiles.clone();
+
+            while try_move_and_update_rock(
+                &mut moving_rock,
+                &self.grid,
+                self.rules,
+                &HashSet::new(),
+                &mut tiles,
+            ) && !moving_rock.fallen
+            {
+                cells.push(moving_rock.pos);
+
+                if let Some(direction) = moving_rock.split.take() {
+                    if self.rules.clone_split_rocks && clones.insert((moving_rock.pos, direction)) {
+                        rolls.push(MovingRock::new(moving_rock.pos, direction));
+                    }
+                }
+            }
+        }
+
+        self.rolled_over.insert(pos, cells.clone());
+
+        cells
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use classes::Level;
+    use game_classes::{DiagonalTilt, WinCondition};
+    use strum::VariantNames;
+
+    use crate::assets::{load_map_data, prepare_map};
+
+    use super::*;
+
+    fn map_data_from_str(map: &str) -> (MapData, MapState) {
+        let mut map_data = MapData::new(
+            Map::from_str(map).expect("Map should parse"),
+            WinCondition {
+                general: GeneralWinConditions::default(),
+                rocks: RockWinConditions::Pos(Vec::new()),
+            },
+            Vec::new(),
+        );
+        let state = prepare_map(&mut map_data);
+
+        (map_data, state)
+    }
+
+    #[test]
+    fn rocks_reach_cells_they_roll_over() {
+        let (map_data, state) = map_data_from_str(
+            "\
+o . #
+. # .
+. . .
+",
+        );
+
+        assert_eq!(
+            reachable_cells(&map_data, &state)[&Pos::new(0, 0)],
+            HashSet::from([
+                Pos::new(0, 0),
+                Pos::new(1, 0),
+                Pos::new(0, 1),
+                Pos::new(0, 2),
+                Pos::new(1, 2),
+                Pos::new(2, 2),
+                Pos::new(2, 1),
+            ])
+        );
+    }
+
+    #[test]
+    fn rocks_reach_cells_through_conveyors_splitters_and_switches() {
+        let (mut map_data, state) = map_data_from_str(
+            "\
+o * ⇒ | .
+# # # . #
+# # # + #
+",
+        );
+        map_data.win.general.clone_split_rocks = true;
+        map_data.win.rocks = RockWinConditions::Pos(vec![Pos::new(3, 2)]);
+
+        let mut search = ReachSearch::new(&map_data);
+
+        assert_eq!(
+            search.reachable_from([Pos::new(0, 0)]),
+            HashSet::from([
+                Pos::new(0, 0),
+                Pos::new(1, 0),
+                Pos::new(2, 0),
+                Pos::new(3, 0),
+                Pos::new(4, 0),
+                Pos::new(3, 1),
+                Pos::new(3, 2),
+            ])
+        );
+        // Rolling over the switch doesn't flip it for the rest of the search
+        assert!(search.tiles.flipped_switches.is_empty());
+        assert!(unreachable_targets(&map_data, &state).is_empty());
+        assert!(stranded_rocks(&map_data, &state).is_empty());
+    }
+
+    #[test]
+    fn gates_make_targets_unreachable() {
+        let (map_data, state) = map_data_from_str(
+            "\
++ ← o
+# . #
+",
+        );
+
+        assert!(unreachable_targets(&map_data, &state).is_empty());
+
+        let (map_data, state) = map_data_from_str(
+            "\
++ → o
+# . #
+",
+        );
+
+        assert_eq!(unreachable_targets(&map_data, &state), vec![Pos::new(0, 0)]);
+    }
+
+    #[test]
+    fn diagonal_tilts_reach_past_corners() {
+        let (mut map_data, state) = map_data_from_str(
+            "\
+o #
+# +
+",
+        );
+
+        assert_eq!(unreachable_targets(&map_data, &state), vec![Pos::new(1, 1)]);
+
+        map_data.win.general.diagonal_tilt = DiagonalTilt::On;
+        assert_eq!(unreachable_targets(&map_data, &state), vec![Pos::new(1, 1)]);
+
+        map_data.win.general.diagonal_tilt = DiagonalTilt::Squeeze;
+        assert!(unreachable_targets(&map_data, &state).is_empty());
+    }
+
+    #[test]
+    fn colored_targets_need_a_rock_of_their_color() {
+        let (mut map_data, state) = map_data_from_str("r . b\n");
+        map_data.win.rocks = RockWinConditions::Colored(vec![
+            (Pos::new(1, 0), RockColor::Red),
+            (Pos::new(1, 0), RockColor::Green),
+        ]);
+
+        assert_eq!(unreachable_targets(&map_data, &state), vec![Pos::new(1, 0)]);
+    }
+
+    #[test]
+    fn rocks_without_a_target_are_stranded() {
+        let (map_data, state) = map_data_from_str(
+            "\
+o # + . o
+# # # # .
+",
+        );
+
+        assert_eq!(stranded_rocks(&map_data, &state), vec![Pos::new(0, 0)]);
+    }
+
+    #[test]
+    fn every_level_target_can_be_reached() {
+        for level in Level::VARIANTS {
+            let level: Level = level.parse().expect("Level names should parse");
+            if matches!(level, Level::Lv99) {
+                // The generated stress test level has its target under a wall
+                continue;
+            }
+
+            let (map_data, state) = load_map_data(level).expect("Level should load");
+
+            assert_eq!(
+                unreachable_targets(&map_data, &state),
+                Vec::new(),
+                "{level:?}"
+            );
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use classes::{LostReason, RoundResult, RoundStats};
 use game_classes::{MapState, RockWinConditions, WinCondition};
 
@@ -6,11 +8,42 @@ pub(super) fn check_result(
     state: &MapState,
     round_stats: &RoundStats,
 ) -> Option<RoundResult> {
+    if state.lost_rocks > 0 && !win.general.allow_rock_loss {
+        return Some(RoundResult::Lost(LostReason::RockLost));
+    }
+
     match &win.rocks {
-        RockWinConditions::Pos(pos) => pos
-            .iter()
-            .all(|pos| state.rock_positions.contains(pos))
-            .then_some(RoundResult::Won),
+        RockWinConditions::Pos(pos) => {
+            // A target can be listed more than once, but one rock is enough for it
+            if state.rock_positions.len() < pos.iter().collect::<HashSet<_>>().len() {
+                return Some(RoundResult::Lost(LostReason::RockLost));
+            }
+
+            pos.iter()
+                .all(|pos| state.rock_positions.contains(pos))
+                .then_some(RoundResult::Won)
+        }
+        RockWinConditions::Colored(targets) => {
+            let rocks_missing = targets.iter().any(|(_, color)| {
+                let rocks = state.rock_colors.values().filter(|c| *c == color).count();
+                let targets = targets
+                    .iter()
+                    .filter(|(_, c)| c == color)
+                    .map(|(pos, _)| pos)
+                    .collect::<HashSet<_>>()
+                    .len();
+
+                rocks < targets
+            });
+            if rocks_missing {
+                return Some(RoundResult::Lost(LostReason::RockLost));
+            }
+
+            targets
+                .iter()
+                .all(|(pos, color)| state.rock_colors.get(pos) == Some(color))
+                .then_some(RoundResult::Won)
+        }
         RockWinConditions::Exit(_) => todo!(),
     }
     .or_else(|| {
@@ -23,3 +56,137 @@ pub(super) fn check_result(
         None
     })
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use game_classes::GeneralWinConditions;
+    use maps::prelude::{Pos, RockColor};
+
+    use super::*;
+
+    fn win_on(targets: Vec<Pos>, allow_rock_loss: bool) -> WinCondition {
+        WinCondition {
+            general: GeneralWinConditions {
+                allow_rock_loss,
+                ..Default::default()
+            },
+            rocks: RockWinConditions::Pos(targets),
+        }
+    }
+
+    #[test]
+    fn losing_a_rock() {
+        let state = MapState {
+            rock_positions: HashSet::from([Pos::new(0, 0)]),
+            lost_rocks: 1,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            check_result(
+                &win_on(vec![Pos::new(0, 0)], false),
+                &state,
+                &RoundStats::default()
+            ),
+            Some(RoundResult::Lost(LostReason::RockLost))
+        ));
+        assert!(matches!(
+            check_result(
+                &win_on(vec![Pos::new(0, 0)], true),
+                &state,
+                &RoundStats::default()
+            ),
+            Some(RoundResult::Won)
+        ));
+    }
+
+    #[test]
+    fn not_enough_rocks_for_targets() {
+        let state = MapState {
+            rock_positions: HashSet::from([Pos::new(0, 0)]),
+            lost_rocks: 1,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            check_result(
+                &win_on(vec![Pos::new(1, 0), Pos::new(2, 0)], true),
+                &state,
+                &RoundStats::default()
+            ),
+            Some(RoundResult::Lost(LostReason::RockLost))
+        ));
+    }
+
+    #[test]
+    fn repeated_targets_need_one_rock() {
+        let state = MapState {
+            rock_positions: HashSet::from([Pos::new(0, 0)]),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            check_result(
+                &win_on(vec![Pos::new(0, 0), Pos::new(0, 0)], false),
+                &state,
+                &RoundStats::default()
+            ),
+            Some(RoundResult::Won)
+        ));
+
+        let win = WinCondition {
+            general: GeneralWinConditions::default(),
+            rocks: RockWinConditions::Colored(vec![
+                (Pos::new(0, 0), RockColor::Red),
+                (Pos::new(0, 0), RockColor::Red),
+            ]),
+        };
+        let state = MapState {
+            rock_colors: HashMap::from([(Pos::new(0, 0), RockColor::Red)]),
+            ..state
+        };
+
+        assert!(matches!(
+            check_result(&win, &state, &RoundStats::default()),
+            Some(RoundResult::Won)
+        ));
+    }
+
+    #[test]
+    fn colored_targets_need_matching_rocks() {
+        let win = WinCondition {
+            general: GeneralWinConditions::default(),
+            rocks: RockWinConditions::Colored(vec![
+                (Pos::new(0, 0), RockColor::Red),
+                (Pos::new(1, 0), RockColor::Blue),
+            ]),
+        };
+        let mut state = MapState {
+            rock_positions: HashSet::from([Pos::new(0, 0), Pos::new(1, 0)]),
+            rock_colors: HashMap::from([
+                (Pos::new(0, 0), RockColor::Blue),
+                (Pos::new(1, 0), RockColor::Red),
+            ]),
+            ..Default::default()
+        };
+
+        assert!(check_result(&win, &state, &RoundStats::default()).is_none());
+
+        state.rock_colors = HashMap::from([
+            (Pos::new(0, 0), RockColor::Red),
+            (Pos::new(1, 0), RockColor::Blue),
+        ]);
+        assert!(matches!(
+            check_result(&win, &state, &RoundStats::default()),
+            Some(RoundResult::Won)
+        ));
+
+        state.rock_colors = HashMap::from([(Pos::new(0, 0), RockColor::Red)]);
+        assert!(matches!(
+            check_result(&win, &state, &RoundStats::default()),
+            Some(RoundResult::Lost(LostReason::RockLost))
+        ));
+    }
+}
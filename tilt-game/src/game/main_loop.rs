@@ -5,7 +5,10 @@ use console::{style, Key, Term};
 use classes::{Level, RoundResult, RoundStats};
 use game_classes::{MapData, MapState};
 
-use super::{data::saving, logic::print_map};
+use super::{
+    data::saving,
+    logic::{print_map, stranded_rocks, unreachable_targets},
+};
 use crate::{assets::load_map_data, cli::Action, Error, Result};
 
 /// Starts the Game in the current Terminal
@@ -50,9 +53,45 @@ fn run_main_loop(term: &Term, term_err: &Term) -> Result<()> {
                     continue;
                 };
 
-                map_data = load_level(level, term, &mut stats)?;
+                match load_level(level, term, &mut stats) {
+                    Ok(new_map_data) => map_data = new_map_data,
+                    Err(err) => {
+                        term_err.write_line(&format!("{err}"))?;
+                        continue;
+                    }
+                }
                 current_level = level;
             }
+            Action::CheckLevel(level) => {
+                let Ok(level) = Level::from_str(&level) else {
+                    term_err.write_line(&format!("{}", Error::LevelNotFound(level)))?;
+                    continue;
+                };
+
+                let (check_data, check_state) = match load_map_data(level) {
+                    Ok(loaded) => loaded,
+                    Err(err) => {
+                        term_err.write_line(&format!("{err}"))?;
+                        continue;
+                    }
+                };
+
+                let unreachable = unreachable_targets(&check_data, &check_state);
+                if unreachable.is_empty() {
+                    term.write_line(&format!("Every target of {level:?} can be reached"))?;
+                } else {
+                    term_err.write_line(&format!(
+                        "No rock can reach the targets {unreachable:?} of {level:?}"
+                    ))?;
+                }
+
+                let stranded = stranded_rocks(&check_data, &check_state);
+                if !stranded.is_empty() {
+                    term.write_line(&format!(
+                        "The rocks {stranded:?} of {level:?} can't reach any target"
+                    ))?;
+                }
+            }
             Action::Result(RoundResult::Won) => {
                 term.write_line(&style("Level won!").on_green().to_string())?;
 
@@ -68,7 +107,14 @@ fn run_main_loop(term: &Term, term_err: &Term) -> Result<()> {
                 if term.read_key()? == Key::Char('r') {
                     map_data = reload_level(current_level, term, &mut stats)?;
                 } else {
-                    map_data = load_level(next_level, term, &mut stats)?;
+                    match load_level(next_level, term, &mut stats) {
+                        Ok(new_map_data) => map_data = new_map_data,
+                        Err(err) => {
+                            term_err.write_line(&format!("{err}"))?;
+                            map_data = reload_level(current_level, term, &mut stats)?;
+                            continue;
+                        }
+                    }
                     current_level = next_level;
                 }
             }
@@ -84,7 +130,7 @@ fn run_main_loop(term: &Term, term_err: &Term) -> Result<()> {
                 map_data = reload_level(current_level, term, &mut stats)?;
             }
             Action::Quit => break,
-        };
+        }
     }
 
     Ok(())
@@ -99,7 +145,7 @@ fn reload_level(
 }
 
 fn load_level(level: Level, term: &Term, stats: &mut RoundStats) -> Result<(MapData, MapState)> {
-    let map_data = load_map_data(level);
+    let map_data = load_map_data(level)?;
     *stats = RoundStats::default();
 
     print_map(term, &map_data.0, &map_data.1, stats)?;